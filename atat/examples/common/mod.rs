@@ -1,5 +1,7 @@
 pub mod general;
 
+use core::sync::atomic::{AtomicU32, Ordering};
+
 use atat::atat_derive::AtatUrc;
 
 use atat::atat_derive::{AtatCmd, AtatResp};
@@ -16,3 +18,19 @@ pub enum Urc {
     #[at_urc("+UMWI")]
     MessageWaitingIndication(general::urc::MessageWaitingIndication),
 }
+
+static TICKS: AtomicU32 = AtomicU32::new(0);
+
+/// Advance the millisecond clock. Must be called from a 1 kHz timer interrupt.
+pub fn tick() {
+    TICKS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Clone)]
+pub struct MillisClock;
+
+impl atat::Clock for MillisClock {
+    fn now_ms(&self) -> u32 {
+        TICKS.load(Ordering::Relaxed)
+    }
+}
@@ -22,15 +22,16 @@ use hal::{
 };
 
 use atat::{
-    digest::DefaultDigester, urc_matcher::DefaultUrcMatcher, ClientBuilder, ComQueue, Queues,
-    ResQueue, UrcQueue,
+    ClientBuilder, ComQueue, DefaultDigester, DefaultUrcMatcher, Queues, ResQueue, UrcQueue,
 };
 
-use heapless::{consts, spsc::Queue};
+use heapless::spsc::Queue;
 
 use crate::rt::entry;
 
-static mut INGRESS: Option<atat::IngressManager<consts::U256>> = None;
+static mut INGRESS: Option<
+    atat::IngressManager<256, DefaultDigester, DefaultUrcMatcher, 10, common::MillisClock>,
+> = None;
 static mut RX: Option<Rx<USART2>> = None;
 
 #[entry]
@@ -61,7 +62,7 @@ fn main() -> ! {
     let rx = gpioa.pa3.into_af7(&mut gpioa.moder, &mut gpioa.afrl);
 
     let mut timer = Timer::tim7(p.TIM7, 1.hz(), clocks, &mut rcc.apb1r1);
-    let at_timer = Timer::tim6(p.TIM6, 100.hz(), clocks, &mut rcc.apb1r1);
+    let mut clock_timer = Timer::tim6(p.TIM6, 1.khz(), clocks, &mut rcc.apb1r1);
 
     let mut serial = Serial::usart2(
         p.USART2,
@@ -73,9 +74,9 @@ fn main() -> ! {
 
     serial.listen(Rxne);
 
-    static mut RES_QUEUE: ResQueue<consts::U256> = Queue(heapless::i::Queue::u8());
-    static mut URC_QUEUE: UrcQueue<consts::U256, consts::U10> = Queue(heapless::i::Queue::u8());
-    static mut COM_QUEUE: ComQueue = Queue(heapless::i::Queue::u8());
+    static mut RES_QUEUE: ResQueue<256> = Queue::new();
+    static mut URC_QUEUE: UrcQueue<256, 10> = Queue::new();
+    static mut COM_QUEUE: ComQueue = Queue::new();
 
     let queues = Queues {
         res_queue: unsafe { RES_QUEUE.split() },
@@ -84,15 +85,21 @@ fn main() -> ! {
     };
 
     let (tx, rx) = serial.split();
-    let (mut client, ingress) =
-        ClientBuilder::new(tx, at_timer, atat::Config::new(atat::Mode::Timeout)).build(queues);
+    let (mut client, ingress) = ClientBuilder::new(
+        tx,
+        common::MillisClock,
+        atat::Config::new(atat::Mode::Timeout),
+    )
+    .build(queues);
 
     unsafe { INGRESS = Some(ingress) };
     unsafe { RX = Some(rx) };
 
     // configure NVIC interrupts
     unsafe { cortex_m::peripheral::NVIC::unmask(hal::stm32::Interrupt::TIM7) };
+    unsafe { cortex_m::peripheral::NVIC::unmask(hal::stm32::Interrupt::TIM6_DACUNDER) };
     timer.listen(Event::TimeOut);
+    clock_timer.listen(Event::TimeOut);
 
     // if all goes well you should reach this breakpoint
     asm::bkpt();
@@ -109,6 +116,11 @@ fn main() -> ! {
     }
 }
 
+#[interrupt]
+fn TIM6_DACUNDER() {
+    common::tick();
+}
+
 #[interrupt]
 fn TIM7() {
     let ingress = unsafe { INGRESS.as_mut().unwrap() };
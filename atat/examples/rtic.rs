@@ -14,29 +14,30 @@ use hal::{
     pac::{Peripherals, USART2},
     prelude::*,
     serial::{Config, Event::Rxne, Rx, Serial},
-    timer::Timer,
+    timer::{Event, Timer},
 };
 
 use atat::{
-    digest::DefaultDigester, urc_matcher::DefaultUrcMatcher, ClientBuilder, ComQueue, Queues,
-    ResQueue, UrcQueue,
+    ClientBuilder, ComQueue, DefaultDigester, DefaultUrcMatcher, Queues, ResQueue, UrcQueue,
 };
 use rtic::{app, export::wfi};
 
-use heapless::{consts, spsc::Queue};
+use heapless::spsc::Queue;
 
 #[app(device = hal::pac, peripherals = true)]
 const APP: () = {
     struct Resources {
-        ingress: atat::IngressManager<consts::U256>,
+        ingress:
+            atat::IngressManager<256, DefaultDigester, DefaultUrcMatcher, 10, common::MillisClock>,
         rx: Rx<USART2>,
+        clock_timer: Timer<hal::stm32::TIM6>,
     }
 
     #[init(spawn = [at_loop])]
     fn init(ctx: init::Context) -> init::LateResources {
-        static mut RES_QUEUE: ResQueue<consts::U256> = Queue(heapless::i::Queue::u8());
-        static mut URC_QUEUE: UrcQueue<consts::U256, consts::U10> = Queue(heapless::i::Queue::u8());
-        static mut COM_QUEUE: ComQueue = Queue(heapless::i::Queue::u8());
+        static mut RES_QUEUE: ResQueue<256> = Queue::new();
+        static mut URC_QUEUE: UrcQueue<256, 10> = Queue::new();
+        static mut COM_QUEUE: ComQueue = Queue::new();
 
         let p = Peripherals::take().unwrap();
 
@@ -63,7 +64,8 @@ const APP: () = {
         let tx = gpioa.pa2.into_af7(&mut gpioa.moder, &mut gpioa.afrl);
         let rx = gpioa.pa3.into_af7(&mut gpioa.moder, &mut gpioa.afrl);
 
-        let mut timer = Timer::tim7(p.TIM7, 1.hz(), clocks, &mut rcc.apb1r1);
+        let mut clock_timer = Timer::tim6(p.TIM6, 1.khz(), clocks, &mut rcc.apb1r1);
+        clock_timer.listen(Event::TimeOut);
 
         let mut serial = Serial::usart2(
             p.USART2,
@@ -82,14 +84,22 @@ const APP: () = {
         };
 
         let (tx, rx) = serial.split();
-        let (mut client, ingress) =
-            ClientBuilder::new(tx, timer, atat::Config::new(atat::Mode::Timeout)).build(queues);
+        let (mut client, ingress) = ClientBuilder::new(
+            tx,
+            common::MillisClock,
+            atat::Config::new(atat::Mode::Timeout),
+        )
+        .build(queues);
 
         ctx.spawn.at_loop().unwrap();
 
         let response = client.send(&common::AT).unwrap();
 
-        init::LateResources { ingress, rx }
+        init::LateResources {
+            ingress,
+            rx,
+            clock_timer,
+        }
     }
 
     #[idle]
@@ -110,6 +120,12 @@ const APP: () = {
             .unwrap();
     }
 
+    #[task(binds = TIM6_DACUNDER, priority = 5, resources = [clock_timer])]
+    fn clock_tick(ctx: clock_tick::Context) {
+        ctx.resources.clock_timer.clear_interrupt(Event::TimeOut);
+        common::tick();
+    }
+
     #[task(binds = USART2, priority = 4, resources = [ingress, rx])]
     fn serial_irq(mut ctx: serial_irq::Context) {
         let rx = ctx.resources.rx;
@@ -1,15 +1,14 @@
-use heapless::ArrayLength;
+use embedded_hal::digital::OutputPin;
 
 use crate::{
     digest::{DefaultDigester, Digester},
-    queues::UrcItem,
     urc_matcher::{DefaultUrcMatcher, UrcMatcher},
-    Client, Config, IngressManager, Queues,
+    Client, Clock, Config, IngressManager, NoPin, Queues,
 };
 
-type ClientParser<Tx, T, U, D, BufLen, UrcCapacity> = (
-    Client<Tx, T, BufLen, UrcCapacity>,
-    IngressManager<BufLen, D, U, UrcCapacity>,
+type ClientParser<Tx, C, U, D, const BUF_LEN: usize, const URC_QUEUE_LEN: usize, P> = (
+    Client<Tx, C, BUF_LEN, URC_QUEUE_LEN, P>,
+    IngressManager<BUF_LEN, D, U, URC_QUEUE_LEN, C>,
 );
 
 /// Builder to set up a [`Client`] and [`IngressManager`] pair.
@@ -19,64 +18,58 @@ type ClientParser<Tx, T, U, D, BufLen, UrcCapacity> = (
 /// [`Client`]: struct.Client.html
 /// [`IngressManager`]: struct.IngressManager.html
 /// [`new`]: #method.new
-pub struct ClientBuilder<Tx, T, U, D, BufLen, UrcCapacity>
+pub struct ClientBuilder<Tx, C, U, D, const BUF_LEN: usize, const URC_QUEUE_LEN: usize, P = NoPin>
 where
     Tx: embedded_hal::serial::Write<u8>,
-    T: embedded_hal::timer::CountDown,
-    T::Time: From<u32>,
+    C: Clock,
     U: UrcMatcher,
     D: Digester,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    P: OutputPin,
 {
     serial_tx: Tx,
-    timer: T,
+    clock: C,
     config: Config,
     custom_urc_matcher: U,
     custom_digester: D,
-    #[doc(hidden)]
-    _internal: core::marker::PhantomData<(BufLen, UrcCapacity)>,
+    wake_up_pin: P,
 }
 
-impl<Tx, T, BufLen, UrcCapacity>
-    ClientBuilder<Tx, T, DefaultUrcMatcher, DefaultDigester, BufLen, UrcCapacity>
+impl<Tx, C, const BUF_LEN: usize, const URC_QUEUE_LEN: usize>
+    ClientBuilder<Tx, C, DefaultUrcMatcher, DefaultDigester, BUF_LEN, URC_QUEUE_LEN, NoPin>
 where
     Tx: embedded_hal::serial::Write<u8>,
-    T: embedded_hal::timer::CountDown,
-    T::Time: From<u32>,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    C: Clock,
 {
     /// Create a builder for new Atat client instance.
     ///
     /// The `serial_tx` type must implement the `embedded_hal`
-    /// [`serial::Write<u8>`][serialwrite] trait while the timer must implement
-    /// the [`timer::CountDown`][timercountdown] trait.
+    /// [`serial::Write<u8>`][serialwrite] trait while the clock must implement
+    /// the [`Clock`] trait. The clock is shared between the client and the
+    /// ingress manager, and thus needs to be `Clone` in order to [`build`].
     ///
     /// [serialwrite]: ../embedded_hal/serial/trait.Write.html
-    /// [timercountdown]: ../embedded_hal/timer/trait.CountDown.html
-    pub fn new(serial_tx: Tx, timer: T, config: Config) -> Self {
+    /// [`Clock`]: trait.Clock.html
+    /// [`build`]: #method.build
+    pub fn new(serial_tx: Tx, clock: C, config: Config) -> Self {
         Self {
             serial_tx,
-            timer,
+            clock,
             config,
             custom_urc_matcher: DefaultUrcMatcher::default(),
             custom_digester: DefaultDigester::default(),
-            #[doc(hidden)]
-            _internal: core::marker::PhantomData,
+            wake_up_pin: NoPin,
         }
     }
 }
 
-impl<Tx, T, U, D, BufLen, UrcCapacity> ClientBuilder<Tx, T, U, D, BufLen, UrcCapacity>
+impl<Tx, C, U, D, const BUF_LEN: usize, const URC_QUEUE_LEN: usize, P>
+    ClientBuilder<Tx, C, U, D, BUF_LEN, URC_QUEUE_LEN, P>
 where
     Tx: embedded_hal::serial::Write<u8>,
-    T: embedded_hal::timer::CountDown,
-    T::Time: From<u32>,
+    C: Clock,
     U: UrcMatcher,
     D: Digester,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    P: OutputPin,
 {
     /// Use a custom [`UrcMatcher`] implementation.
     ///
@@ -84,15 +77,14 @@ where
     pub fn with_custom_urc_matcher<U2: UrcMatcher>(
         self,
         matcher: U2,
-    ) -> ClientBuilder<Tx, T, U2, D, BufLen, UrcCapacity> {
+    ) -> ClientBuilder<Tx, C, U2, D, BUF_LEN, URC_QUEUE_LEN, P> {
         ClientBuilder {
             serial_tx: self.serial_tx,
-            timer: self.timer,
+            clock: self.clock,
             config: self.config,
             custom_urc_matcher: matcher,
             custom_digester: self.custom_digester,
-            #[doc(hidden)]
-            _internal: self._internal,
+            wake_up_pin: self.wake_up_pin,
         }
     }
 
@@ -102,15 +94,32 @@ where
     pub fn with_custom_digester<D2: Digester>(
         self,
         digester: D2,
-    ) -> ClientBuilder<Tx, T, U, D2, BufLen, UrcCapacity> {
+    ) -> ClientBuilder<Tx, C, U, D2, BUF_LEN, URC_QUEUE_LEN, P> {
         ClientBuilder {
             custom_urc_matcher: self.custom_urc_matcher,
             serial_tx: self.serial_tx,
-            timer: self.timer,
+            clock: self.clock,
             config: self.config,
             custom_digester: digester,
-            #[doc(hidden)]
-            _internal: self._internal,
+            wake_up_pin: self.wake_up_pin,
+        }
+    }
+
+    /// Use an output pin to wake up the module, when configured with
+    /// [`WakeUp::Pin`].
+    ///
+    /// [`WakeUp::Pin`]: enum.WakeUp.html#variant.Pin
+    pub fn with_wake_up_pin<P2: OutputPin>(
+        self,
+        pin: P2,
+    ) -> ClientBuilder<Tx, C, U, D, BUF_LEN, URC_QUEUE_LEN, P2> {
+        ClientBuilder {
+            custom_urc_matcher: self.custom_urc_matcher,
+            serial_tx: self.serial_tx,
+            clock: self.clock,
+            config: self.config,
+            custom_digester: self.custom_digester,
+            wake_up_pin: pin,
         }
     }
 
@@ -120,22 +129,28 @@ where
     /// [`IngressManager`]: struct.IngressManager.html
     pub fn build(
         self,
-        queues: Queues<BufLen, UrcCapacity>,
-    ) -> ClientParser<Tx, T, U, D, BufLen, UrcCapacity> {
+        queues: Queues<BUF_LEN, URC_QUEUE_LEN>,
+    ) -> ClientParser<Tx, C, U, D, BUF_LEN, URC_QUEUE_LEN, P>
+    where
+        C: Clone,
+    {
         let parser = IngressManager::with_customs(
             queues.res_queue.0,
             queues.urc_queue.0,
             queues.com_queue.1,
             self.custom_urc_matcher,
             self.custom_digester,
+            self.clock.clone(),
+            self.config,
         );
-        let client = Client::new(
+        let client = Client::with_customs(
             self.serial_tx,
             queues.res_queue.1,
             queues.urc_queue.1,
             queues.com_queue.0,
-            self.timer,
+            self.clock,
             self.config,
+            self.wake_up_pin,
         );
 
         (client, parser)
@@ -1,11 +1,46 @@
-use embedded_hal::{serial, timer::CountDown};
+use embedded_hal::{digital::OutputPin, serial};
 
-use crate::queues::{ComProducer, ResConsumer, UrcConsumer, UrcItem};
+use crate::queues::{ComProducer, ResConsumer, UrcConsumer, UrcQueueLen};
 use crate::traits::{AtatClient, AtatCmd, AtatUrc};
-use crate::{error::Error, queues::ResCapacity};
-use crate::{Command, Config};
-use heapless::{consts, ArrayLength};
-use typenum::Unsigned;
+use crate::{error::Error, queues::RES_CAPACITY};
+use crate::{Clock, Command, Config, NoPin, WakeUp};
+use heapless::Vec;
+
+/// Writes command bytes straight to the serial port, keeping the start of the
+/// command for logging
+struct SerialWriter<'a, Tx> {
+    tx: &'a mut Tx,
+    head: Vec<u8, 50>,
+}
+
+impl<Tx> serde_at::ser::Write for SerialWriter<'_, Tx>
+where
+    Tx: serial::Write<u8>,
+{
+    fn write_bytes(&mut self, bytes: &[u8]) -> serde_at::ser::Result<()> {
+        let room = self.head.capacity() - self.head.len();
+        self.head
+            .extend_from_slice(&bytes[..bytes.len().min(room)])
+            .ok();
+        for &c in bytes {
+            nb::block!(self.tx.try_write(c)).map_err(|_e| serde_at::ser::Error::Write)?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the command bytes, failing with `BufferFull` past `MAX` bytes
+struct LenCheck<const MAX: usize>(usize);
+
+impl<const MAX: usize> serde_at::ser::Write for LenCheck<MAX> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> serde_at::ser::Result<()> {
+        self.0 += bytes.len();
+        if self.0 > MAX {
+            return Err(serde_at::ser::Error::BufferFull);
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, PartialEq)]
 enum ClientState {
@@ -29,44 +64,72 @@ pub enum Mode {
 /// some spsc queue consumers, where any received responses can be dequeued. The
 /// Client also has an spsc producer, to allow signaling commands like
 /// `reset` to the ingress-manager.
-pub struct Client<Tx, T, BufLen = consts::U256, UrcCapacity = consts::U10>
+pub struct Client<Tx, C, const BUF_LEN: usize = 256, const URC_QUEUE_LEN: usize = 10, P = NoPin>
 where
     Tx: serial::Write<u8>,
-    T: CountDown,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    C: Clock,
+    P: OutputPin,
 {
     /// Serial writer
     tx: Tx,
 
     /// The response consumer receives responses from the ingress manager
-    res_c: ResConsumer<BufLen>,
+    res_c: ResConsumer<BUF_LEN>,
     /// The URC consumer receives URCs from the ingress manager
-    urc_c: UrcConsumer<BufLen, UrcCapacity>,
+    urc_c: UrcConsumer<BUF_LEN, URC_QUEUE_LEN>,
     /// The command producer can send commands to the ingress manager
     com_p: ComProducer,
 
     state: ClientState,
-    timer: T,
+    clock: C,
     config: Config,
+
+    /// Instant of the last received response or URC, used for `cmd_cooldown`
+    last_receive: Option<u32>,
+    /// Instant the pending command was sent, used for command timeouts
+    cmd_sent: u32,
+    /// Instant of the last activity on the link, used for `wake_up_idle`
+    last_activity: Option<u32>,
+
+    /// Pin used by the `WakeUp::Pin` policy
+    wake_up_pin: P,
 }
 
-impl<Tx, T, BufLen, UrcCapacity> Client<Tx, T, BufLen, UrcCapacity>
+impl<Tx, C, const BUF_LEN: usize, const URC_QUEUE_LEN: usize>
+    Client<Tx, C, BUF_LEN, URC_QUEUE_LEN, NoPin>
 where
     Tx: serial::Write<u8>,
-    T: CountDown,
-    T::Time: From<u32>,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    C: Clock,
 {
     pub fn new(
         tx: Tx,
-        res_c: ResConsumer<BufLen>,
-        urc_c: UrcConsumer<BufLen, UrcCapacity>,
+        res_c: ResConsumer<BUF_LEN>,
+        urc_c: UrcConsumer<BUF_LEN, URC_QUEUE_LEN>,
         com_p: ComProducer,
-        timer: T,
+        clock: C,
         config: Config,
     ) -> Self {
+        Self::with_customs(tx, res_c, urc_c, com_p, clock, config, NoPin)
+    }
+}
+
+impl<Tx, C, const BUF_LEN: usize, const URC_QUEUE_LEN: usize, P>
+    Client<Tx, C, BUF_LEN, URC_QUEUE_LEN, P>
+where
+    Tx: serial::Write<u8>,
+    C: Clock,
+    P: OutputPin,
+{
+    pub fn with_customs(
+        tx: Tx,
+        res_c: ResConsumer<BUF_LEN>,
+        urc_c: UrcConsumer<BUF_LEN, URC_QUEUE_LEN>,
+        com_p: ComProducer,
+        clock: C,
+        config: Config,
+        wake_up_pin: P,
+    ) -> Self {
+        let () = UrcQueueLen::<URC_QUEUE_LEN>::ASSERT;
         Self {
             tx,
             res_c,
@@ -74,21 +137,177 @@ where
             com_p,
             state: ClientState::Idle,
             config,
-            timer,
+            clock,
+            last_receive: None,
+            cmd_sent: 0,
+            last_activity: None,
+            wake_up_pin,
+        }
+    }
+
+    /// Record a response or URC being received, starting the command cooldown
+    fn mark_received(&mut self) {
+        let now = self.clock.now_ms();
+        self.last_receive = Some(now);
+        self.last_activity = Some(now);
+    }
+
+    /// Block for `ms` milliseconds
+    fn delay(&self, ms: u32) {
+        let start = self.clock.now_ms();
+        while self.clock.elapsed_ms(start) < ms {}
+    }
+
+    /// Check if a response is enqueued from the ingress manager, timing out
+    /// if `config.mode` is Timeout
+    fn poll_response<A: AtatCmd<LEN>, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+    ) -> nb::Result<(), Error<A::Error>> {
+        if self.res_c.ready() {
+            return Ok(());
+        }
+        if let Mode::Timeout = self.config.mode {
+            if self.clock.elapsed_ms(self.cmd_sent) >= cmd.max_timeout_ms() {
+                self.state = ClientState::Idle;
+                // Tell the parser to reset to initial state due to timeout
+                if self.com_p.enqueue(Command::Reset).is_err() {
+                    // TODO: Consider how to act in this situation.
+                    defmt::error!("Failed to signal parser to clear buffer on timeout!");
+                }
+                return Err(nb::Error::Other(Error::Timeout));
+            }
         }
+        Err(nb::Error::WouldBlock)
+    }
+
+    /// Parse the enqueued response and pass it to `f`. The response may
+    /// borrow from the queue, so it is only dequeued once `f` has returned.
+    fn take_response<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R,
+    {
+        self.mark_received();
+        let awaiting = self.state == ClientState::AwaitingResponse;
+        self.state = ClientState::Idle;
+        let result = match self.res_c.peek() {
+            Some(result) => match cmd.parse(result.as_deref()) {
+                Ok(resp) if awaiting => Ok(f(resp)),
+                Ok(_) => {
+                    // FIXME: Is this correct?
+                    defmt::error!("Is this correct?! WouldBlock");
+                    Err(nb::Error::WouldBlock)
+                }
+                Err(e) => Err(nb::Error::Other(e)),
+            },
+            None => return Err(nb::Error::WouldBlock),
+        };
+        self.res_c.dequeue();
+        result
+    }
+
+    /// Block until at least `config.cmd_cooldown` ms have passed since the
+    /// last response or URC was received
+    fn wait_cooldown(&self) {
+        if let Some(last_receive) = self.last_receive {
+            while self.clock.elapsed_ms(last_receive) < self.config.cmd_cooldown {}
+        }
+    }
+
+    /// Whether the link has been idle for longer than `config.wake_up_idle`
+    fn is_idle(&self) -> bool {
+        self.last_activity.map_or(true, |last_activity| {
+            self.clock.elapsed_ms(last_activity) >= self.config.wake_up_idle
+        })
+    }
+
+    /// Apply the configured wake-up policy, if the link has been idle for
+    /// longer than `config.wake_up_idle`
+    fn wake_up<E: defmt::Format>(&mut self) -> Result<(), Error<E>> {
+        if self.config.wake_up == WakeUp::Disabled || !self.is_idle() {
+            return Ok(());
+        }
+
+        defmt::debug!("Waking up module after idle period");
+
+        match self.config.wake_up {
+            WakeUp::Disabled => {}
+            WakeUp::Byte { byte, delay_ms } => {
+                nb::block!(self.tx.try_write(byte)).map_err(|_e| Error::Write)?;
+                nb::block!(self.tx.try_flush()).map_err(|_e| Error::Write)?;
+                self.delay(delay_ms);
+            }
+            WakeUp::Command {
+                timeout_ms,
+                delay_ms,
+            } => {
+                for &c in b"AT\r" {
+                    nb::block!(self.tx.try_write(c)).map_err(|_e| Error::Write)?;
+                }
+                nb::block!(self.tx.try_flush()).map_err(|_e| Error::Write)?;
+
+                // Wait for the response to the wake-up command, and discard
+                // it, so it is not taken as the response to the command
+                let start = self.clock.now_ms();
+                while !self.res_c.ready() && self.clock.elapsed_ms(start) < timeout_ms {}
+                if self.res_c.dequeue().is_some() {
+                    self.mark_received();
+                } else {
+                    defmt::warn!("No response to the wake-up command");
+                    // Drop any partial response to the wake-up command
+                    if self.com_p.enqueue(Command::Reset).is_err() {
+                        defmt::error!("Failed to signal parser to clear buffer on wake-up!");
+                    }
+                }
+                self.delay(delay_ms);
+            }
+            WakeUp::Pin { pulse_ms, delay_ms } => {
+                self.wake_up_pin.try_set_low().ok();
+                self.delay(pulse_ms);
+                self.wake_up_pin.try_set_high().ok();
+                self.delay(delay_ms);
+            }
+        }
+        Ok(())
     }
 }
 
-impl<Tx, T, BufLen, UrcCapacity> AtatClient for Client<Tx, T, BufLen, UrcCapacity>
+impl<Tx, C, const BUF_LEN: usize, const URC_QUEUE_LEN: usize, P> AtatClient
+    for Client<Tx, C, BUF_LEN, URC_QUEUE_LEN, P>
 where
     Tx: serial::Write<u8>,
-    T: CountDown,
-    T::Time: From<u32>,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    C: Clock,
+    P: OutputPin,
 {
-    fn send<A: AtatCmd>(&mut self, cmd: &A) -> nb::Result<A::Response, Error<A::Error>> {
+    fn send_with<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R,
+    {
         if let ClientState::Idle = self.state {
+            cmd.validate()?;
+            // Serialize the command once without sending it, so commands
+            // failing to serialize, or longer than `LEN`, are rejected before
+            // anything is written to the serial port
+            cmd.write_into(&mut LenCheck::<LEN>(0))?;
+
+            // compare the time of the last response or URC and ensure at least
+            // `self.config.cmd_cooldown` ms have passed before sending a new
+            // command
+            self.wait_cooldown();
+            self.wake_up()?;
+
+            // Only force the receive state once any response to the wake-up
+            // command has been discarded
             if cmd.force_receive_state() && self.com_p.enqueue(Command::ForceReceiveState).is_err()
             {
                 // TODO: Consider how to act in this situation.
@@ -97,46 +316,41 @@ where
                 );
             }
 
-            // compare the time of the last response or URC and ensure at least
-            // `self.config.cmd_cooldown` ms have passed before sending a new
-            // command
-            nb::block!(self.timer.try_wait()).ok();
-            let cmd_buf = cmd.as_bytes();
-
-            if cmd_buf.len() < 50 {
-                defmt::debug!("Sending command: \"{=[u8]:a}\"", &cmd_buf);
+            // Stream the command straight to the serial port, rather than
+            // materialising it on the stack
+            let mut writer = SerialWriter {
+                tx: &mut self.tx,
+                head: Vec::new(),
+            };
+            let len = cmd.write_into(&mut writer)?;
+            if len < 50 {
+                defmt::debug!("Sent command: \"{=[u8]:a}\"", &writer.head);
             } else {
-                defmt::debug!(
-                    "Sending command with too long payload ({} bytes) to log!",
-                    cmd_buf.len()
-                );
-            }
-
-            for c in cmd_buf {
-                nb::block!(self.tx.try_write(c)).map_err(|_e| Error::Write)?;
+                defmt::debug!("Sent command with too long payload ({} bytes) to log!", len);
             }
             nb::block!(self.tx.try_flush()).map_err(|_e| Error::Write)?;
+            self.cmd_sent = self.clock.now_ms();
+            self.last_activity = Some(self.cmd_sent);
             self.state = ClientState::AwaitingResponse;
         }
 
         if !cmd.expects_response_code() {
             self.state = ClientState::Idle;
-            return cmd.parse(Ok(&[])).map_err(nb::Error::Other);
+            return cmd.parse(Ok(&[])).map(f).map_err(nb::Error::Other);
         }
 
         match self.config.mode {
-            Mode::Blocking => Ok(nb::block!(self.check_response(cmd))?),
-            Mode::NonBlocking => self.check_response(cmd),
-            Mode::Timeout => {
-                self.timer.try_start(cmd.max_timeout_ms()).ok();
-                Ok(nb::block!(self.check_response(cmd))?)
-            }
+            Mode::Blocking | Mode::Timeout => nb::block!(self.poll_response(cmd))?,
+            Mode::NonBlocking => self.poll_response(cmd)?,
         }
+        self.take_response(cmd, f)
     }
 
     fn peek_urc_with<URC: AtatUrc, F: FnOnce(URC::Response) -> bool>(&mut self, f: F) {
+        let now = self.clock.now_ms();
         if let Some(urc) = self.urc_c.peek() {
-            self.timer.try_start(self.config.cmd_cooldown).ok();
+            self.last_receive = Some(now);
+            self.last_activity = Some(now);
             if let Some(urc) = URC::parse(urc) {
                 if !f(urc) {
                     return;
@@ -148,39 +362,17 @@ where
         }
     }
 
-    fn check_response<A: AtatCmd>(&mut self, cmd: &A) -> nb::Result<A::Response, Error<A::Error>> {
-        if let Some(result) = self.res_c.dequeue() {
-            return cmd
-                .parse(result.as_deref())
-                .map_err(nb::Error::from)
-                .and_then(|r| {
-                    if let ClientState::AwaitingResponse = self.state {
-                        self.timer.try_start(self.config.cmd_cooldown).ok();
-                        self.state = ClientState::Idle;
-                        Ok(r)
-                    } else {
-                        // FIXME: Is this correct?
-                        defmt::error!("Is this correct?! WouldBlock");
-                        Err(nb::Error::WouldBlock)
-                    }
-                })
-                .map_err(|e| {
-                    self.timer.try_start(self.config.cmd_cooldown).ok();
-                    self.state = ClientState::Idle;
-                    e
-                });
-        } else if let Mode::Timeout = self.config.mode {
-            if self.timer.try_wait().is_ok() {
-                self.state = ClientState::Idle;
-                // Tell the parser to reset to initial state due to timeout
-                if self.com_p.enqueue(Command::Reset).is_err() {
-                    // TODO: Consider how to act in this situation.
-                    defmt::error!("Failed to signal parser to clear buffer on timeout!");
-                }
-                return Err(nb::Error::Other(Error::Timeout));
-            }
-        }
-        Err(nb::Error::WouldBlock)
+    fn check_response_with<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R,
+    {
+        self.poll_response(cmd)?;
+        self.take_response(cmd, f)
     }
 
     fn get_mode(&self) -> Mode {
@@ -193,12 +385,12 @@ where
             defmt::error!("Failed to signal ingress manager to reset!");
         }
 
-        for _ in 0..ResCapacity::USIZE {
+        for _ in 0..RES_CAPACITY {
             if self.res_c.dequeue().is_none() {
                 break;
             }
         }
-        for _ in 0..UrcCapacity::USIZE {
+        for _ in 0..self.urc_c.capacity() {
             if self.urc_c.dequeue().is_none() {
                 break;
             }
@@ -215,31 +407,28 @@ mod test {
         atat_derive::{AtatCmd, AtatEnum, AtatResp, AtatUrc},
         GenericError,
     };
-    use heapless::{consts, spsc::Queue, String, Vec};
+    use heapless::{spsc::Queue, String, Vec};
     use nb;
 
-    struct CdMock;
+    /// Clock advancing by one millisecond every time it is read
+    struct ClockMock {
+        time: core::cell::Cell<u32>,
+    }
 
-    impl CountDown for CdMock {
-        type Error = core::convert::Infallible;
-        type Time = u32;
-        fn try_start<T>(&mut self, _count: T) -> Result<(), Self::Error>
-        where
-            T: Into<Self::Time>,
-        {
-            Ok(())
-        }
-        fn try_wait(&mut self) -> nb::Result<(), Self::Error> {
-            Ok(())
+    impl Clock for ClockMock {
+        fn now_ms(&self) -> u32 {
+            let now = self.time.get();
+            self.time.set(now.wrapping_add(1));
+            now
         }
     }
 
     struct TxMock {
-        s: String<consts::U64>,
+        s: String<64>,
     }
 
     impl TxMock {
-        fn new(s: String<consts::U64>) -> Self {
+        fn new(s: String<64>) -> Self {
             TxMock { s }
         }
     }
@@ -262,7 +451,7 @@ mod test {
     }
 
     impl core::str::FromStr for InnerError {
-        // This error will always get mapped to `atat::Error::Parse`
+        // This error will always get mapped to `atat::Error::Parse(..)`
         type Err = ();
 
         fn from_str(_s: &str) -> Result<Self, Self::Err> {
@@ -302,6 +491,12 @@ mod test {
         pub rst: Option<ResetMode>,
     }
     #[derive(Clone, AtatCmd)]
+    #[at_cmd("+CSGT", NoResponse)]
+    pub struct SetGreetingText<'a> {
+        #[at_arg(len = 16, max_len = 16, charset = "[ -~]")]
+        pub text: &'a str,
+    }
+    #[derive(Clone, AtatCmd)]
     #[at_cmd("+CUN", TestResponseString, timeout_ms = 180000)]
     pub struct TestRespStringCmd {
         #[at_arg(position = 0)]
@@ -318,9 +513,32 @@ mod test {
         pub rst: Option<ResetMode>,
     }
 
-    // #[derive(Clone, AtatCmd)]
-    // #[at_cmd("+CUN", TestResponseStringMixed, timeout_ms = 180000)]
-    // pub struct TestUnnamedStruct(Functionality, Option<ResetMode>);
+    #[derive(Clone, AtatCmd)]
+    #[at_cmd("+CMGS", NoResponse)]
+    pub struct TooLongCmd<'a> {
+        #[at_arg(len = 4)]
+        pub data: &'a str,
+    }
+
+    #[derive(Clone, AtatCmd)]
+    #[at_cmd("+CMGS", NoResponse, force_receive_state = true)]
+    pub struct ForcedCmd;
+
+    #[derive(Clone, AtatCmd)]
+    #[at_cmd("+USORD", SocketData<'a>)]
+    pub struct ReadSocketData {
+        #[at_arg(position = 0)]
+        pub socket: u8,
+        #[at_arg(position = 1)]
+        pub length: usize,
+    }
+
+    #[derive(Clone, AtatCmd)]
+    #[at_cmd("+CUN", TestUnnamedResponse, timeout_ms = 180000)]
+    pub struct TestUnnamedStruct(
+        #[at_arg(position = 1)] Functionality,
+        #[at_arg(position = 0)] Option<ResetMode>,
+    );
 
     #[derive(Clone, PartialEq, AtatEnum)]
     #[at_enum(u8)]
@@ -352,7 +570,7 @@ mod test {
         #[at_arg(position = 1)]
         pub length: usize,
         #[at_arg(position = 2)]
-        pub data: Vec<u8, TestRxBufLen>,
+        pub data: Vec<u8, TEST_RX_BUF_LEN>,
     }
 
     #[derive(Clone, AtatResp, PartialEq, Debug)]
@@ -362,7 +580,7 @@ mod test {
         #[at_arg(position = 1)]
         pub length: usize,
         #[at_arg(position = 2)]
-        pub data: String<consts::U64>,
+        pub data: String<64>,
     }
 
     #[derive(Clone, AtatResp, PartialEq, Debug)]
@@ -372,7 +590,20 @@ mod test {
         #[at_arg(position = 2)]
         pub length: usize,
         #[at_arg(position = 0)]
-        pub data: String<consts::U64>,
+        pub data: String<64>,
+    }
+
+    #[derive(Clone, AtatResp, PartialEq, Debug)]
+    pub struct TestUnnamedResponse(u8, String<16>);
+
+    #[derive(Clone, AtatResp, PartialEq, Debug)]
+    pub struct SocketData<'a> {
+        #[at_arg(position = 0)]
+        pub socket: u8,
+        #[at_arg(position = 1)]
+        pub length: usize,
+        #[at_arg(position = 2)]
+        pub data: &'a str,
     }
 
     #[derive(Clone, AtatResp)]
@@ -389,22 +620,24 @@ mod test {
         MessageWaitingIndication(MessageWaitingIndication),
     }
 
-    type TestRxBufLen = consts::U256;
-    type TestUrcCapacity = consts::U10;
+    const TEST_RX_BUF_LEN: usize = 256;
+    const TEST_URC_QUEUE_LEN: usize = 10;
 
     macro_rules! setup {
         ($config:expr) => {{
-            static mut RES_Q: queues::ResQueue<TestRxBufLen> = Queue(heapless::i::Queue::u8());
+            static mut RES_Q: queues::ResQueue<TEST_RX_BUF_LEN> = Queue::new();
             let (res_p, res_c) = unsafe { RES_Q.split() };
-            static mut URC_Q: queues::UrcQueue<TestRxBufLen, TestUrcCapacity> =
-                Queue(heapless::i::Queue::u8());
+            static mut URC_Q: queues::UrcQueue<TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> = Queue::new();
             let (urc_p, urc_c) = unsafe { URC_Q.split() };
-            static mut COM_Q: queues::ComQueue = Queue(heapless::i::Queue::u8());
+            static mut COM_Q: queues::ComQueue = Queue::new();
             let (com_p, _com_c) = unsafe { COM_Q.split() };
 
             let tx_mock = TxMock::new(String::new());
-            let client: Client<TxMock, CdMock, TestRxBufLen, TestUrcCapacity> =
-                Client::new(tx_mock, res_c, urc_c, com_p, CdMock, $config);
+            let clock = ClockMock {
+                time: core::cell::Cell::new(u32::MAX - 100),
+            };
+            let client: Client<TxMock, ClockMock, TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> =
+                Client::new(tx_mock, res_c, urc_c, com_p, clock, $config);
             (client, res_p, urc_p)
         }};
     }
@@ -453,7 +686,7 @@ mod test {
             rst: Some(ResetMode::DontReset),
         };
 
-        p.enqueue(Ok(Vec::<u8, TestRxBufLen>::new())).unwrap();
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
 
         assert_eq!(client.state, ClientState::Idle);
         assert_eq!(client.send(&cmd), Ok(NoResponse));
@@ -461,11 +694,11 @@ mod test {
 
         assert_eq!(
             client.tx.s,
-            String::<consts::U32>::from("AT+CFUN=4,0\r\n"),
+            String::<32>::from("AT+CFUN=4,0\r\n"),
             "Wrong encoding of string"
         );
 
-        p.enqueue(Ok(Vec::<u8, TestRxBufLen>::new())).unwrap();
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
 
         let cmd = Test2Cmd {
             fun: Functionality::DM,
@@ -475,13 +708,32 @@ mod test {
 
         assert_eq!(
             client.tx.s,
-            String::<consts::U32>::from("AT+CFUN=4,0\r\nAT+FUN=1,6\r\n"),
+            String::<32>::from("AT+CFUN=4,0\r\nAT+FUN=1,6\r\n"),
             "Reverse order string did not match"
         );
     }
 
     #[test]
-    #[ignore]
+    fn serialize_error() {
+        let (mut client, _, _) = setup!(Config::new(Mode::Blocking));
+
+        let cmd = TooLongCmd { data: "0123456789" };
+        assert_eq!(cmd.try_as_bytes(), Err(Error::Overflow));
+
+        // Nothing is written, and the client remains ready for the next command
+        assert_eq!(client.send(&cmd), Err(nb::Error::Other(Error::Overflow)));
+        assert_eq!(client.state, ClientState::Idle);
+        assert!(client.tx.s.is_empty());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Failed to serialize command")]
+    fn serialize_error_as_bytes() {
+        TooLongCmd { data: "0123456789" }.as_bytes();
+    }
+
+    #[test]
     fn countdown() {
         let (mut client, _, _) = setup!(Config::new(Mode::Timeout));
 
@@ -492,14 +744,30 @@ mod test {
             rst: Some(ResetMode::Reset),
         };
         assert_eq!(client.send(&cmd), Err(nb::Error::Other(Error::Timeout)));
-
-        match client.config.mode {
-            Mode::Timeout => {} // assert_eq!(cd_mock.time, 180000),
-            _ => panic!("Wrong AT mode"),
-        }
+        assert!(client.clock.elapsed_ms(client.cmd_sent) >= 180_000);
         assert_eq!(client.state, ClientState::Idle);
     }
 
+    #[test]
+    fn cooldown() {
+        let (mut client, mut p, _) = setup!(Config::new(Mode::Timeout).cmd_cooldown(500));
+
+        let cmd = SetModuleFunctionality {
+            fun: Functionality::APM,
+            rst: Some(ResetMode::DontReset),
+        };
+
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
+        assert_eq!(client.send(&cmd), Ok(NoResponse));
+        let received = client.last_receive.unwrap();
+
+        // The cooldown must not be affected by the timeout of the previous command
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
+        assert_eq!(client.send(&cmd), Ok(NoResponse));
+        assert!(client.cmd_sent.wrapping_sub(received) >= 500);
+        assert!(client.cmd_sent.wrapping_sub(received) < 600);
+    }
+
     #[test]
     fn blocking() {
         let (mut client, mut p, _) = setup!(Config::new(Mode::Blocking));
@@ -509,12 +777,28 @@ mod test {
             rst: Some(ResetMode::DontReset),
         };
 
-        p.enqueue(Ok(Vec::<u8, TestRxBufLen>::new())).unwrap();
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
 
         assert_eq!(client.state, ClientState::Idle);
         assert_eq!(client.send(&cmd), Ok(NoResponse));
         assert_eq!(client.state, ClientState::Idle);
-        assert_eq!(client.tx.s, String::<consts::U32>::from("AT+CFUN=4,0\r\n"));
+        assert_eq!(client.tx.s, String::<32>::from("AT+CFUN=4,0\r\n"));
+    }
+
+    #[test]
+    fn invalid_argument() {
+        let (mut client, _, _) = setup!(Config::new(Mode::Blocking));
+
+        let cmd = SetGreetingText {
+            text: "Hello\r\nAT+CFUN=0",
+        };
+
+        assert_eq!(
+            client.send(&cmd),
+            Err(nb::Error::Other(Error::InvalidArgument("text")))
+        );
+        assert_eq!(client.state, ClientState::Idle);
+        assert_eq!(client.tx.s, String::<32>::new());
     }
 
     #[test]
@@ -532,7 +816,7 @@ mod test {
 
         assert_eq!(client.check_response(&cmd), Err(nb::Error::WouldBlock));
 
-        p.enqueue(Ok(Vec::<u8, TestRxBufLen>::new())).unwrap();
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
 
         assert_eq!(client.state, ClientState::AwaitingResponse);
 
@@ -552,7 +836,7 @@ mod test {
         };
 
         let response =
-            Vec::<u8, TestRxBufLen>::from_slice(b"+CUN: 22,16,\"0123456789012345\"").unwrap();
+            Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+CUN: 22,16,\"0123456789012345\"").unwrap();
         p.enqueue(Ok(response)).unwrap();
 
         assert_eq!(client.state, ClientState::Idle);
@@ -566,7 +850,7 @@ mod test {
             })
         );
         assert_eq!(client.state, ClientState::Idle);
-        assert_eq!(client.tx.s, String::<consts::U32>::from("AT+CFUN=4,0\r\n"));
+        assert_eq!(client.tx.s, String::<32>::from("AT+CFUN=4,0\r\n"));
     }
     // Test response containing string
     #[test]
@@ -580,7 +864,7 @@ mod test {
         };
 
         let response =
-            Vec::<u8, TestRxBufLen>::from_slice(b"+CUN: 22,16,\"0123456789012345\"").unwrap();
+            Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+CUN: 22,16,\"0123456789012345\"").unwrap();
         p.enqueue(Ok(response)).unwrap();
 
         assert_eq!(client.state, ClientState::Idle);
@@ -590,7 +874,7 @@ mod test {
             Ok(TestResponseString {
                 socket: 22,
                 length: 16,
-                data: String::<consts::U64>::from("0123456789012345")
+                data: String::<64>::from("0123456789012345")
             })
         );
         assert_eq!(client.state, ClientState::Idle);
@@ -602,7 +886,7 @@ mod test {
         };
 
         let response =
-            Vec::<u8, TestRxBufLen>::from_slice(b"+CUN: \"0123456789012345\",22,16").unwrap();
+            Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+CUN: \"0123456789012345\",22,16").unwrap();
         p.enqueue(Ok(response)).unwrap();
 
         assert_eq!(
@@ -610,17 +894,96 @@ mod test {
             Ok(TestResponseStringMixed {
                 socket: 22,
                 length: 16,
-                data: String::<consts::U64>::from("0123456789012345")
+                data: String::<64>::from("0123456789012345")
             })
         );
         assert_eq!(client.state, ClientState::Idle);
     }
 
+    #[test]
+    fn borrowed_response() {
+        let (mut client, mut p, _) = setup!(Config::new(Mode::Blocking));
+
+        let cmd = ReadSocketData {
+            socket: 22,
+            length: 16,
+        };
+
+        let response =
+            Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+USORD: 22,16,\"0123456789012345\"").unwrap();
+        p.enqueue(Ok(response)).unwrap();
+
+        assert_eq!(
+            client.send_with(&cmd, |resp| {
+                assert_eq!(resp.socket, 22);
+                String::<16>::from(resp.data)
+            }),
+            Ok(String::from("0123456789012345"))
+        );
+        assert_eq!(client.state, ClientState::Idle);
+        assert_eq!(client.tx.s, String::<32>::from("AT+USORD=22,16\r\n"));
+
+        // The response is only dequeued once it has been handled
+        let response = Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+USORD: 22,4,\"abcd\"").unwrap();
+        p.enqueue(Ok(response)).unwrap();
+
+        assert_eq!(client.send_with(&cmd, |resp| resp.data.len()), Ok(4));
+        assert!(!client.res_c.ready());
+    }
+
+    #[test]
+    fn borrowed_response_non_blocking() {
+        let (mut client, mut p, _) = setup!(Config::new(Mode::NonBlocking));
+
+        let cmd = ReadSocketData {
+            socket: 22,
+            length: 4,
+        };
+
+        assert_eq!(
+            client.send_with(&cmd, |resp| resp.length),
+            Err(nb::Error::WouldBlock)
+        );
+        assert_eq!(
+            client.check_response_with(&cmd, |resp| resp.length),
+            Err(nb::Error::WouldBlock)
+        );
+
+        let response = Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+USORD: 22,4,\"abcd\"").unwrap();
+        p.enqueue(Ok(response)).unwrap();
+
+        assert_eq!(
+            client.check_response_with(&cmd, |resp| resp.data == "abcd"),
+            Ok(true)
+        );
+        assert_eq!(client.state, ClientState::Idle);
+    }
+
+    #[test]
+    fn unnamed_struct() {
+        let (mut client, mut p, _) = setup!(Config::new(Mode::Blocking));
+
+        let cmd = TestUnnamedStruct(Functionality::DM, Some(ResetMode::Reset));
+
+        let response = Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+CUN: 22,\"data\"").unwrap();
+        p.enqueue(Ok(response)).unwrap();
+
+        assert_eq!(
+            client.send(&cmd),
+            Ok(TestUnnamedResponse(22, String::from("data")))
+        );
+        assert_eq!(
+            client.tx.s,
+            String::<32>::from("AT+CUN=1,6\r\n"),
+            "Wrong encoding of tuple struct"
+        );
+    }
+
     #[test]
     fn urc() {
         let (mut client, _, mut urc_p) = setup!(Config::new(Mode::NonBlocking));
 
-        let response = Vec::<u8, TestRxBufLen>::from_slice(b"+UMWI: 0, 1").unwrap();
+        let response = Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"+UMWI: 0, 1").unwrap();
         urc_p.enqueue(response).unwrap();
 
         assert_eq!(client.state, ClientState::Idle);
@@ -628,6 +991,188 @@ mod test {
         assert_eq!(client.state, ClientState::Idle);
     }
 
+    #[test]
+    fn wake_up_byte() {
+        let (mut client, mut p, _) = setup!(Config::new(Mode::Blocking).wake_up(
+            WakeUp::Byte {
+                byte: b'\r',
+                delay_ms: 10
+            },
+            5000
+        ));
+
+        let cmd = SetModuleFunctionality {
+            fun: Functionality::APM,
+            rst: Some(ResetMode::DontReset),
+        };
+
+        p.enqueue(Ok(Vec::<u8, TEST_RX_BUF_LEN>::new())).unwrap();
+        assert_eq!(client.send(&cmd), Ok(NoResponse));
+        assert_eq!(client.tx.s, String::<32>::from("\rAT+CFUN=4,0\r\n"));
+    }
+
+    #[test]
+    fn wake_up_command() {
+        let (mut client, mut p, _) = setup!(Config::new(Mode::NonBlocking).wake_up(
+            WakeUp::Command {
+                timeout_ms: 100,
+                delay_ms: 10
+            },
+            5000
+        ));
+
+        let cmd = SetModuleFunctionality {
+            fun: Functionality::APM,
+            rst: Some(ResetMode::DontReset),
+        };
+
+        // Response to the wake-up command, which should be discarded
+        p.enqueue(Err(InternalError::Error(Vec::new()))).unwrap();
+        assert_eq!(client.send(&cmd), Err(nb::Error::WouldBlock));
+        assert_eq!(client.tx.s, String::<32>::from("AT\rAT+CFUN=4,0\r\n"));
+        assert_eq!(client.state, ClientState::AwaitingResponse);
+    }
+
+    /// Clock enqueueing `replies` once their instant has passed, standing in
+    /// for the ingress manager receiving responses while the client waits.
+    /// `replies` is ordered latest first.
+    ///
+    /// Commands to the ingress manager are recorded in `commands`, along with
+    /// the number of replies enqueued before them.
+    struct ReplyClock {
+        time: core::cell::Cell<u32>,
+        res_p: core::cell::RefCell<queues::ResProducer<TEST_RX_BUF_LEN>>,
+        replies: core::cell::RefCell<Vec<(u32, queues::ResItem<TEST_RX_BUF_LEN>), 2>>,
+        com_c: core::cell::RefCell<queues::ComConsumer>,
+        commands: core::cell::RefCell<Vec<(Command, usize), 4>>,
+        enqueued: core::cell::Cell<usize>,
+    }
+
+    impl Clock for ReplyClock {
+        fn now_ms(&self) -> u32 {
+            let now = self.time.get();
+            self.time.set(now + 1);
+            while let Some(command) = self.com_c.borrow_mut().dequeue() {
+                self.commands
+                    .borrow_mut()
+                    .push((command, self.enqueued.get()))
+                    .unwrap();
+            }
+            let mut replies = self.replies.borrow_mut();
+            if matches!(replies.last(), Some((at, _)) if *at <= now) {
+                let (_, reply) = replies.pop().unwrap();
+                self.res_p.borrow_mut().enqueue(reply).unwrap();
+                self.enqueued.set(self.enqueued.get() + 1);
+            }
+            now
+        }
+    }
+
+    macro_rules! setup_replies {
+        ($config:expr, $replies:expr) => {{
+            static mut RES_Q: queues::ResQueue<TEST_RX_BUF_LEN> = Queue::new();
+            let (res_p, res_c) = unsafe { RES_Q.split() };
+            static mut URC_Q: queues::UrcQueue<TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> = Queue::new();
+            let (_urc_p, urc_c) = unsafe { URC_Q.split() };
+            static mut COM_Q: queues::ComQueue = Queue::new();
+            let (com_p, com_c) = unsafe { COM_Q.split() };
+
+            let clock = ReplyClock {
+                time: core::cell::Cell::new(0),
+                res_p: core::cell::RefCell::new(res_p),
+                replies: core::cell::RefCell::new(Vec::from_slice(&$replies).unwrap()),
+                com_c: core::cell::RefCell::new(com_c),
+                commands: core::cell::RefCell::new(Vec::new()),
+                enqueued: core::cell::Cell::new(0),
+            };
+            let client: Client<TxMock, ReplyClock, TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> =
+                Client::new(
+                    TxMock::new(String::new()),
+                    res_c,
+                    urc_c,
+                    com_p,
+                    clock,
+                    $config,
+                );
+            client
+        }};
+    }
+
+    #[test]
+    fn wake_up_command_late_reply() {
+        // The wake-up response arrives well after `delay_ms`, followed by the
+        // response to the command
+        let mut client = setup_replies!(
+            Config::new(Mode::Timeout).wake_up(
+                WakeUp::Command {
+                    timeout_ms: 500,
+                    delay_ms: 10
+                },
+                5000
+            ),
+            [
+                (300, Ok(Vec::new())),
+                (200, Err(InternalError::Error(Vec::new()))),
+            ]
+        );
+
+        let cmd = SetModuleFunctionality {
+            fun: Functionality::APM,
+            rst: Some(ResetMode::DontReset),
+        };
+
+        assert_eq!(client.send(&cmd), Ok(NoResponse));
+        assert_eq!(client.tx.s, String::<32>::from("AT\rAT+CFUN=4,0\r\n"));
+        assert!(client.clock.replies.borrow().is_empty());
+    }
+
+    #[test]
+    fn wake_up_command_no_reply() {
+        let mut client = setup_replies!(
+            Config::new(Mode::Timeout).wake_up(
+                WakeUp::Command {
+                    timeout_ms: 100,
+                    delay_ms: 10
+                },
+                5000
+            ),
+            [(300, Ok(Vec::new()))]
+        );
+
+        let cmd = SetModuleFunctionality {
+            fun: Functionality::APM,
+            rst: Some(ResetMode::DontReset),
+        };
+
+        // The command is sent after `timeout_ms`, and gets its own response
+        assert_eq!(client.send(&cmd), Ok(NoResponse));
+        assert_eq!(client.tx.s, String::<32>::from("AT\rAT+CFUN=4,0\r\n"));
+        assert_eq!(client.clock.commands.borrow()[..], [(Command::Reset, 0)]);
+    }
+
+    #[test]
+    fn wake_up_command_force_receive_state() {
+        let mut client = setup_replies!(
+            Config::new(Mode::Timeout).wake_up(
+                WakeUp::Command {
+                    timeout_ms: 500,
+                    delay_ms: 10
+                },
+                5000
+            ),
+            [(300, Ok(Vec::new())), (200, Ok(Vec::new()))]
+        );
+
+        // The receive state is only forced after the wake-up response, so it
+        // applies to the response of the command
+        assert_eq!(client.send(&ForcedCmd), Ok(NoResponse));
+        assert_eq!(client.tx.s, String::<32>::from("AT\rAT+CMGS\r\n"));
+        assert_eq!(
+            client.clock.commands.borrow()[..],
+            [(Command::ForceReceiveState, 1)]
+        );
+    }
+
     #[test]
     fn invalid_response() {
         let (mut client, mut p, _) = setup!(Config::new(Mode::Blocking));
@@ -638,11 +1183,18 @@ mod test {
             rst: Some(ResetMode::DontReset),
         };
 
-        let response = Vec::<u8, TestRxBufLen>::from_slice(b"+CUN: 22,16,22").unwrap();
+        // Data longer than the `String<U64>` of the response
+        let response = Vec::<u8, TEST_RX_BUF_LEN>::from_slice(
+            b"+CUN: 22,16,\"0123456789012345678901234567890123456789012345678901234567890123456789\"",
+        )
+        .unwrap();
         p.enqueue(Ok(response)).unwrap();
 
         assert_eq!(client.state, ClientState::Idle);
-        assert_eq!(client.send(&cmd), Err(nb::Error::Other(Error::Parse)));
+        assert!(matches!(
+            client.send(&cmd),
+            Err(nb::Error::Other(Error::Parse(_)))
+        ));
         assert_eq!(client.state, ClientState::Idle);
     }
 }
@@ -0,0 +1,51 @@
+/// A monotonic millisecond clock, used by the client to keep track of command
+/// cooldowns, command timeouts and idle periods independently of each other.
+///
+/// The returned value is allowed to wrap around on overflow, as all durations
+/// are computed using wrapping arithmetic. This means that no single duration
+/// can exceed `u32::MAX` milliseconds (~49 days).
+///
+/// A simple implementation could be a millisecond tick counter incremented
+/// from a periodic timer interrupt:
+///
+/// ```
+/// use atat::Clock;
+/// use core::sync::atomic::{AtomicU32, Ordering};
+///
+/// static TICKS: AtomicU32 = AtomicU32::new(0);
+///
+/// // Call this from a 1 kHz timer interrupt
+/// fn on_tick() {
+///     TICKS.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// #[derive(Clone)]
+/// struct SysTickClock;
+///
+/// impl Clock for SysTickClock {
+///     fn now_ms(&self) -> u32 {
+///         TICKS.load(Ordering::Relaxed)
+///     }
+/// }
+/// ```
+pub trait Clock {
+    /// Milliseconds elapsed since an arbitrary, fixed point in time.
+    fn now_ms(&self) -> u32;
+
+    /// Milliseconds elapsed since the instant `since`, previously returned by
+    /// [`now_ms`](#tymethod.now_ms).
+    fn elapsed_ms(&self, since: u32) -> u32 {
+        self.now_ms().wrapping_sub(since)
+    }
+}
+
+/// Placeholder for an ingress manager without a clock. Time never advances,
+/// meaning that the inter-character timeout never triggers.
+#[derive(Debug, Default, Clone)]
+pub struct NoClock;
+
+impl Clock for NoClock {
+    fn now_ms(&self) -> u32 {
+        0
+    }
+}
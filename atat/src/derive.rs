@@ -1,79 +1,245 @@
-use core::ops::Mul;
-use heapless::{ArrayLength, String, Vec};
-use serde_at::CharVec;
-use typenum::Unsigned;
+use heapless::{String, Vec};
+use serde_at::{
+    BackslashEscape, Base64Bytes, CharVec, Escaped, HexBytes, HexEscape, HexStr, ValueRange,
+};
+
+use crate::digest::OK_LEN;
+use crate::error::ERROR_LEN;
+use crate::{AtatResp, Error};
 
 /// Trait used by [`atat_derive`] to estimate lengths of the serialized commands, at compile time.
 ///
 /// [`atat_derive`]: https://crates.io/crates/atat_derive
 pub trait AtatLen {
-    type Len: ArrayLength<u8>;
+    const LEN: usize;
+
+    /// Number of lines of the type as a response, eg. `N` for the repeated
+    /// lines of a `heapless::Vec<T, N>`, each with the response prefix
+    const LINES: usize = 1;
+}
+
+/// Trait used by [`atat_derive`] to estimate the length of the response to a
+/// command at compile time, from the prefix of the response, eg. `+CUN: `.
+///
+/// [`atat_derive`]: https://crates.io/crates/atat_derive
+pub trait AtatPrefixLen {
+    const LEN: usize;
+
+    /// The response to the command, the same as `AtatCmd::Response`
+    type Response<'a>: AtatResp;
+}
+
+/// Maximum length of the response to `Cmd` in the response buffer, as the
+/// response prefix and line termination of every line, the response itself
+/// and the final `\r\nOK\r\n`
+pub const fn response_len<Cmd>() -> usize
+where
+    Cmd: AtatPrefixLen,
+    Cmd::Response<'static>: AtatLen,
+{
+    let lines = <Cmd::Response<'static> as AtatLen>::LINES;
+    lines * Cmd::LEN
+        + lines.saturating_sub(1) * 2
+        + <Cmd::Response<'static> as AtatLen>::LEN
+        + OK_LEN
+}
+
+/// Fails to compile if the response to `Cmd`, or an error response, doesn't
+/// fit in a response buffer of `BUF_LEN` bytes, rather than having it silently
+/// truncated at runtime.
+///
+/// The response length is estimated from the `AtatLen` implementation of the
+/// response, which is derived by `AtatResp` with `#[at_resp(len)]`. Error
+/// responses are kept in [`InternalError::Error`] with up to 85 bytes.
+///
+/// Example:
+/// ```
+/// use atat::atat_derive::{AtatCmd, AtatResp};
+/// use heapless::String;
+///
+/// #[derive(Clone, AtatResp)]
+/// #[at_resp(len)]
+/// pub struct Manufacturer {
+///     pub id: String<64>,
+/// }
+///
+/// #[derive(Clone, AtatCmd)]
+/// #[at_cmd("+CGMI", Manufacturer)]
+/// pub struct GetManufacturer;
+///
+/// const _: () = atat::assert_buf_len::<GetManufacturer, 256>();
+/// ```
+///
+/// whereas a buffer too small for the manufacturer fails to compile:
+/// ```compile_fail
+/// # use atat::atat_derive::{AtatCmd, AtatResp};
+/// # use heapless::String;
+/// #
+/// # #[derive(Clone, AtatResp)]
+/// # #[at_resp(len)]
+/// # pub struct Manufacturer {
+/// #     pub id: String<64>,
+/// # }
+/// #
+/// # #[derive(Clone, AtatCmd)]
+/// # #[at_cmd("+CGMI", Manufacturer)]
+/// # pub struct GetManufacturer;
+/// #
+/// const _: () = atat::assert_buf_len::<GetManufacturer, 64>();
+/// ```
+///
+/// [`InternalError::Error`]: ../enum.InternalError.html#variant.Error
+pub const fn assert_buf_len<Cmd, const BUF_LEN: usize>()
+where
+    Cmd: AtatPrefixLen,
+    Cmd::Response<'static>: AtatLen,
+{
+    assert!(
+        response_len::<Cmd>() <= BUF_LEN,
+        "the response does not fit in the response buffer"
+    );
+    assert!(
+        ERROR_LEN <= BUF_LEN,
+        "error responses do not fit in the response buffer"
+    );
+}
+
+/// Used by [`atat_derive`] to implement `AtatCmd::as_bytes`. Commands failing
+/// to serialize panic in debug builds, and are logged and returned empty in
+/// release builds.
+///
+/// [`atat_derive`]: https://crates.io/crates/atat_derive
+#[doc(hidden)]
+pub fn bytes_or_empty<E, const LEN: usize>(bytes: Result<Vec<u8, LEN>, Error<E>>) -> Vec<u8, LEN>
+where
+    E: defmt::Format,
+{
+    bytes.unwrap_or_else(|e| {
+        defmt::error!("Failed to serialize command: {}", e);
+        if cfg!(debug_assertions) {
+            panic!("Failed to serialize command, use `try_as_bytes` to handle the error");
+        }
+        Vec::new()
+    })
 }
 
 macro_rules! impl_length {
-    ($type:ty, $len:ident) => {
+    ($type:ty, $len:expr) => {
         #[allow(clippy::use_self)]
         impl AtatLen for $type {
-            type Len = heapless::consts::$len;
+            const LEN: usize = $len;
         }
     };
 }
 
-impl_length!(char, U1);
-impl_length!(bool, U5);
-impl_length!(isize, U19);
-impl_length!(usize, U20);
-impl_length!(u8, U3);
-impl_length!(u16, U5);
-impl_length!(u32, U10);
-impl_length!(u64, U20);
-impl_length!(u128, U39);
-impl_length!(i8, U4);
-impl_length!(i16, U6);
-impl_length!(i32, U11);
-impl_length!(i64, U20);
-impl_length!(i128, U40);
-impl_length!(f32, U42);
-impl_length!(f64, U312);
-
-impl<T: ArrayLength<u8>> AtatLen for String<T> {
-    type Len = T;
+impl_length!(char, 1);
+impl_length!(bool, 5);
+impl_length!(isize, 19);
+impl_length!(usize, 20);
+impl_length!(u8, 3);
+impl_length!(u16, 5);
+impl_length!(u32, 10);
+impl_length!(u64, 20);
+impl_length!(u128, 39);
+impl_length!(i8, 4);
+impl_length!(i16, 6);
+impl_length!(i32, 11);
+impl_length!(i64, 20);
+impl_length!(i128, 40);
+// Floats are serialized in decimal notation, where the longest values are the
+// negative values closest to zero, eg. `-0.000000000000000000000000000000000000011754942`
+impl_length!(f32, 48);
+impl_length!(f64, 327);
+
+/// The string and the quotes
+impl<const N: usize> AtatLen for String<N> {
+    const LEN: usize = N + 2;
 }
 
 impl<T: AtatLen> AtatLen for Option<T> {
-    type Len = T::Len;
+    const LEN: usize = T::LEN;
+    const LINES: usize = T::LINES;
 }
 
 impl<T: AtatLen> AtatLen for &T {
-    type Len = T::Len;
+    const LEN: usize = T::LEN;
+    const LINES: usize = T::LINES;
 }
 
-impl<T, L> AtatLen for Vec<T, L>
-where
-    T: AtatLen,
-    L: ArrayLength<T> + Unsigned + Mul<<T as AtatLen>::Len>,
-    <L as Mul<<T as AtatLen>::Len>>::Output: ArrayLength<u8>,
-{
-    type Len = <L as Mul<<T as AtatLen>::Len>>::Output;
+impl<T: AtatLen, const N: usize> AtatLen for Vec<T, N> {
+    const LEN: usize = N * T::LEN;
+    const LINES: usize = N * T::LINES;
 }
 
-impl<T> AtatLen for CharVec<T>
-where
-    T: ArrayLength<char> + ArrayLength<u8>,
-{
-    type Len = T;
+impl<const N: usize> AtatLen for CharVec<N> {
+    const LEN: usize = N;
+}
+
+// Hexadecimal digits of the largest value, and the quotes. `HexStr` fails to
+// serialize with a `width` beyond that.
+impl_length!(HexStr<u8>, 4);
+impl_length!(HexStr<u16>, 6);
+impl_length!(HexStr<u32>, 10);
+impl_length!(HexStr<u64>, 18);
+impl_length!(HexStr<usize>, 18);
+impl_length!(HexStr<u128>, 34);
+
+/// Every character within the quotes may be escaped as `\hh`
+impl<T: AtatLen> AtatLen for Escaped<T, HexEscape> {
+    const LEN: usize = T::LEN.saturating_sub(2) * 3 + 2;
+}
+
+/// Every character within the quotes may be escaped as `\c`
+impl<T: AtatLen> AtatLen for Escaped<T, BackslashEscape> {
+    const LEN: usize = T::LEN.saturating_sub(2) * 2 + 2;
+}
+
+/// Two hexadecimal digits per byte, and the quotes
+impl<const N: usize> AtatLen for HexBytes<Vec<u8, N>> {
+    const LEN: usize = N * 2 + 2;
+}
+
+/// Four characters per started group of three bytes, and the quotes
+impl<const N: usize> AtatLen for Base64Bytes<Vec<u8, N>> {
+    const LEN: usize = N.div_ceil(3) * 4 + 2;
+}
+
+/// Both values, the `-` and the parentheses, eg. `(0-255)`
+impl<T: AtatLen> AtatLen for ValueRange<T> {
+    const LEN: usize = T::LEN * 2 + 3;
+}
+
+impl AtatLen for () {
+    const LEN: usize = 0;
+}
+
+/// Tuples are serialized as comma separated values, so the length of a tuple
+/// is the length of its elements and a comma between each of them
+macro_rules! impl_tuple_length {
+    ($head:ident $(, $tail:ident)*) => {
+        impl<$head: AtatLen $(, $tail: AtatLen)*> AtatLen for ($head, $($tail,)*) {
+            const LEN: usize = $head::LEN $(+ 1 + $tail::LEN)*;
+        }
+    };
 }
 
+impl_tuple_length!(A);
+impl_tuple_length!(A, B);
+impl_tuple_length!(A, B, C);
+impl_tuple_length!(A, B, C, D);
+impl_tuple_length!(A, B, C, D, E);
+impl_tuple_length!(A, B, C, D, E, F);
+
 #[cfg(test)]
 mod tests {
     use crate as atat;
     use atat::{derive::AtatLen, AtatCmd};
     use atat_derive::{AtatCmd, AtatEnum, AtatResp};
-    use heapless::{consts, String, Vec};
-    use serde_at::{from_str, to_string, SerializeOptions};
-    use typenum::marker_traits::Unsigned;
+    use heapless::{String, Vec};
+    use serde_at::{from_slice, from_str, to_string, SerializeOptions};
 
     #[derive(Debug, PartialEq, AtatResp)]
+    #[at_resp(len)]
     struct NoResponse {}
 
     #[derive(Debug, PartialEq, AtatEnum)]
@@ -104,13 +270,13 @@ mod tests {
         #[at_arg(value = 1)]
         SingleSimpleTuple(u8),
         #[at_arg(default, value = 2)]
-        AdvancedTuple(u8, String<consts::U10>, i64, SimpleEnumU32),
+        AdvancedTuple(u8, String<10>, i64, SimpleEnumU32),
         #[at_arg(value = 3)]
         SingleSimpleStruct { x: u8 },
         #[at_arg(value = 4)]
         AdvancedStruct {
             a: u8,
-            b: String<consts::U10>,
+            b: String<10>,
             c: i64,
             d: SimpleEnum,
         },
@@ -122,7 +288,7 @@ mod tests {
     #[at_cmd("+CFUN", NoResponse)]
     struct LengthTester<'a> {
         x: u8,
-        y: String<consts::U128>,
+        y: String<128>,
         #[at_arg(len = 2)]
         z: u16,
         #[at_arg(len = 150)]
@@ -131,39 +297,974 @@ mod tests {
         b: SimpleEnumU32,
         #[at_arg(len = 3)]
         c: SimpleEnumU32,
-        // d: Vec<SimpleEnumU32, consts::U5>,
+        // d: Vec<SimpleEnumU32, 5>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct Functionality {
+        fun: u8,
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd(
+        "+CFUN",
+        NoResponse,
+        read(GetFunctionality, Functionality),
+        test(TestFunctionality, NoResponse)
+    )]
+    struct SetFunctionality {
+        fun: u8,
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+CGMI", NoResponse, kind = "exec")]
+    struct ManufacturerId;
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd(
+        "+CGATT",
+        NoResponse,
+        kind = "read",
+        cmd_prefix = "",
+        termination = "\r"
+    )]
+    struct GetAttached;
+
+    #[test]
+    fn test_cmd_kinds() {
+        assert_eq!(
+            &SetFunctionality { fun: 1 }.as_bytes()[..],
+            b"AT+CFUN=1\r\n"
+        );
+        assert_eq!(&GetFunctionality.as_bytes()[..], b"AT+CFUN?\r\n");
+        assert_eq!(&TestFunctionality.as_bytes()[..], b"AT+CFUN=?\r\n");
+        assert_eq!(&ManufacturerId.as_bytes()[..], b"AT+CGMI\r\n");
+        assert_eq!(&GetAttached.as_bytes()[..], b"+CGATT?\r");
+
+        assert_eq!(
+            GetFunctionality.parse(Ok(b"+CFUN: 4")),
+            Ok(Functionality { fun: 4 })
+        );
+    }
+
+    #[test]
+    fn test_serialize_into() {
+        let mut buf = [0; 16];
+        let len = SetFunctionality { fun: 1 }
+            .serialize_into(&mut buf)
+            .unwrap();
+        assert_eq!(&buf[..len], b"AT+CFUN=1\r\n");
+        assert_eq!(
+            SetFunctionality { fun: 1 }.serialize_into(&mut buf[..8]),
+            Err(atat::Error::Overflow)
+        );
+
+        let mut buf = Vec::<u8, 16>::new();
+        assert_eq!(GetAttached.write_into(&mut buf), Ok(8));
+        assert_eq!(&buf[..], b"+CGATT?\r");
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct SignalQuality(u8, u8);
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct Rssi(u8);
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+UTEST", SignalQuality)]
+    struct TupleCmd(u8, (u8, bool));
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+UTEST", Rssi)]
+    struct ReorderedTupleCmd(#[at_arg(position = 1)] u8, #[at_arg(position = 0)] u16);
+
+    #[test]
+    fn test_tuple_structs() {
+        assert_eq!(
+            &TupleCmd(1, (2, false)).as_bytes()[..],
+            b"AT+UTEST=1,2,false\r\n"
+        );
+        assert_eq!(&ReorderedTupleCmd(1, 2).as_bytes()[..], b"AT+UTEST=2,1\r\n");
+        assert_eq!(<TupleCmd as AtatLen>::LEN, 3 + (3 + 5 + 1) + 1);
+
+        assert_eq!(
+            TupleCmd(1, (2, false)).parse(Ok(b"+UTEST: 21,99")),
+            Ok(SignalQuality(21, 99))
+        );
+        assert!(matches!(
+            TupleCmd(1, (2, false)).parse(Ok(b"+UTEST: 21")),
+            Err(atat::Error::Parse(_))
+        ));
+        assert_eq!(ReorderedTupleCmd(1, 2).parse(Ok(b"+UTEST: 7")), Ok(Rssi(7)));
+        assert_eq!(from_str::<NoResponse>(""), Ok(NoResponse {}));
+    }
+
+    #[test]
+    fn test_parse_error_context() {
+        let err = match GetFunctionality.parse(Ok(b"+CFUN: x")) {
+            Err(atat::Error::Parse(e)) => e,
+            _ => panic!("expected a parse error"),
+        };
+
+        if cfg!(feature = "parse-error-context") {
+            assert_eq!(err.kind(), Some(&serde_at::de::Error::InvalidType));
+            assert_eq!(err.index(), Some(7));
+            assert_eq!(err.field(), Some("fun"));
+        } else {
+            assert_eq!(err.kind(), None);
+            assert_eq!(err.index(), None);
+            assert_eq!(err.field(), None);
+        }
+
+        let err = match TupleCmd(1, (2, false)).parse(Ok(b"+UTEST: 21,-1")) {
+            Err(atat::Error::Parse(e)) => e,
+            _ => panic!("expected a parse error"),
+        };
+        if cfg!(feature = "parse-error-context") {
+            assert!(err.kind().is_some());
+            assert_eq!(err.index(), Some(11));
+            assert_eq!(err.field(), Some("1"));
+        }
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct PdpContext {
+        cid: u8,
+        pdp_type: String<8>,
+        apn: Option<String<16>>,
+        #[at_arg(default)]
+        addr: String<16>,
+        #[at_arg(default)]
+        d_comp: SimpleEnum,
+        #[at_arg(default)]
+        h_comp: u8,
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+CGDCONT", NoResponse)]
+    struct SetPdpContext<'a> {
+        cid: u8,
+        #[at_arg(len = 8)]
+        pdp_type: Option<&'a str>,
+        #[at_arg(len = 16)]
+        apn: Option<&'a str>,
+    }
+
+    #[test]
+    fn test_empty_params() {
+        assert_eq!(
+            from_str::<PdpContext>("+CGDCONT: 1,\"IP\",,,3,"),
+            Ok(PdpContext {
+                cid: 1,
+                pdp_type: String::from("IP"),
+                apn: None,
+                addr: String::new(),
+                d_comp: SimpleEnum::D,
+                h_comp: 0,
+            })
+        );
+        assert_eq!(
+            from_str::<PdpContext>("+CGDCONT: 1,\"IP\",\"internet\""),
+            Ok(PdpContext {
+                cid: 1,
+                pdp_type: String::from("IP"),
+                apn: Some(String::from("internet")),
+                addr: String::new(),
+                d_comp: SimpleEnum::A,
+                h_comp: 0,
+            })
+        );
+
+        let cmd = SetPdpContext {
+            cid: 1,
+            pdp_type: None,
+            apn: Some("internet"),
+        };
+        assert_eq!(cmd.as_bytes(), b"AT+CGDCONT=1,,\"internet\"\r\n"[..]);
+
+        let cmd = SetPdpContext {
+            cid: 1,
+            pdp_type: Some("IP"),
+            apn: None,
+        };
+        assert_eq!(cmd.as_bytes(), b"AT+CGDCONT=1,\"IP\"\r\n"[..]);
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+UGPIOC", NoResponse)]
+    struct SetGpioMask {
+        #[at_arg(format = "hex", width = 4, upper_case, quoted = true)]
+        mask: u16,
+        #[at_arg(format = "bin", width = 4)]
+        bits: u8,
+        #[at_arg(radix = 36)]
+        id: Option<u32>,
+        hex: serde_at::HexStr<u32>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct Registration {
+        stat: u8,
+        #[at_arg(format = "hex")]
+        lac: u16,
+        #[at_arg(format = "hex")]
+        ci: Option<u32>,
+        #[at_arg(format = "hex", default)]
+        rac: u8,
+        act: u8,
+    }
+
+    #[test]
+    fn test_radix_format() {
+        let cmd = SetGpioMask {
+            mask: 0xab,
+            bits: 5,
+            id: Some(35),
+            hex: serde_at::HexStr {
+                val: 0xff,
+                ..serde_at::HexStr::default()
+            },
+        };
+        assert_eq!(cmd.as_bytes(), b"AT+UGPIOC=\"00AB\",0101,z,ff\r\n"[..]);
+        assert_eq!(<SetGpioMask as AtatLen>::LEN, 6 + 8 + 7 + 10 + 3);
+
+        assert_eq!(
+            from_str::<Registration>("+CREG: 1,\"1a2B\",\"00C3D4E5\",,7"),
+            Ok(Registration {
+                stat: 1,
+                lac: 0x1a2b,
+                ci: Some(0x00c3_d4e5),
+                rac: 0,
+                act: 7,
+            })
+        );
+        assert_eq!(
+            from_str::<Registration>("+CREG: 1,FFFF,,1F,7"),
+            Ok(Registration {
+                stat: 1,
+                lac: 0xffff,
+                ci: None,
+                rac: 0x1f,
+                act: 7,
+            })
+        );
+        assert!(from_str::<Registration>("+CREG: 1,10000,,,7").is_err());
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+UGPOS", NoResponse)]
+    struct SetPosition {
+        #[at_arg(precision = 6)]
+        lat: f64,
+        #[at_arg(precision = 6)]
+        lon: f64,
+        #[at_arg(precision = 1)]
+        alt: Option<f32>,
+        acc: f32,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct Position {
+        lat: f64,
+        lon: f64,
+        alt: Option<f32>,
+    }
+
+    #[test]
+    fn test_float_precision() {
+        let cmd = SetPosition {
+            lat: 55.671_234_6,
+            lon: -12.5,
+            alt: Some(20.04),
+            acc: 2.5,
+        };
+        assert_eq!(
+            cmd.as_bytes(),
+            b"AT+UGPOS=55.671235,-12.500000,20.0,2.5\r\n"[..]
+        );
+        assert_eq!(<SetPosition as AtatLen>::LEN, 317 + 317 + 42 + 48 + 3);
+
+        // The largest values fit, at any precision up to the maximum
+        let cmd = SetPosition {
+            lat: f64::MAX,
+            lon: f64::MIN,
+            alt: Some(f32::MIN),
+            acc: 2.5,
+        };
+        assert!(cmd.try_as_bytes().is_ok());
+        let s: String<64> = serde_at::to_string(
+            &serde_at::Fixed(&f32::MIN, 7),
+            String::<0>::new(),
+            SerializeOptions {
+                value_sep: false,
+                cmd_prefix: "",
+                termination: "",
+            },
+        )
+        .unwrap();
+        assert_eq!(s.len(), serde_at::fixed_len::<f32>(7));
+
+        assert_eq!(
+            from_str::<Position>("+UGPOS: 55.671235,-12.500000"),
+            Ok(Position {
+                lat: 55.671_235,
+                lon: -12.5,
+                alt: None,
+            })
+        );
+    }
+
+    /// Module specific scheme, only escaping quotes
+    #[derive(Debug, Default, Clone, Copy, PartialEq)]
+    struct QuoteEscape;
+
+    impl serde_at::EscapeScheme for QuoteEscape {
+        fn escape(&self, c: char, out: &mut dyn core::fmt::Write) -> core::fmt::Result {
+            match c {
+                '"' => out.write_str("\\q"),
+                c => out.write_char(c),
+            }
+        }
+
+        fn unescape(&self, s: &str) -> Option<(char, usize)> {
+            match s.as_bytes().first()? {
+                b'q' => Some(('"', 1)),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+UWSEC", NoResponse)]
+    struct SetCredentials<'a> {
+        #[at_arg(escape = "hex")]
+        ssid: String<32>,
+        #[at_arg(escape = "backslash", len = 64)]
+        password: &'a str,
+        #[at_arg(escape = "QuoteEscape", len = 32)]
+        name: String<16>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct Credentials {
+        #[at_arg(escape = "hex")]
+        ssid: String<32>,
+        #[at_arg(escape = "backslash")]
+        password: String<32>,
+        #[at_arg(escape = "QuoteEscape", default)]
+        name: String<16>,
+    }
+
+    #[test]
+    fn test_escape() {
+        let cmd = SetCredentials {
+            ssid: String::from("My \"Home\", 5G"),
+            password: "p\\ss\"word",
+            name: String::from("\"x\""),
+        };
+        assert_eq!(
+            cmd.as_bytes(),
+            b"AT+UWSEC=\"My \\22Home\\22, 5G\",\"p\\\\ss\\\"word\",\"\\qx\\q\"\r\n"[..]
+        );
+        // Escaped characters within the quotes
+        assert_eq!(
+            <SetCredentials<'_> as AtatLen>::LEN,
+            (32 * 3 + 2) + 64 + 32 + 2
+        );
+
+        assert_eq!(
+            from_slice::<Credentials>(
+                b"+UWSEC: \"My \\22Home\\22, 5G\",\"p\\\\ss\\\"word\",\"\\qx\\q\""
+            ),
+            Ok(Credentials {
+                ssid: String::from("My \"Home\", 5G"),
+                password: String::from("p\\ss\"word"),
+                name: String::from("\"x\""),
+            })
+        );
+        assert_eq!(
+            from_slice::<Credentials>(b"+UWSEC: \"a\",\"b\""),
+            Ok(Credentials {
+                ssid: String::from("a"),
+                password: String::from("b"),
+                name: String::new(),
+            })
+        );
+        assert!(from_slice::<Credentials>(b"+UWSEC: \"a\\2\",\"b\"").is_err());
+    }
+
+    #[derive(Debug, PartialEq, AtatEnum)]
+    enum PinStatus {
+        #[at_arg(value = "READY")]
+        Ready,
+        #[at_arg(value = "SIM PIN")]
+        SimPin,
+        #[at_arg(value = "SIM PUK")]
+        SimPuk,
+    }
+
+    #[derive(Debug, PartialEq, AtatEnum)]
+    enum IpMode {
+        #[at_arg(value = "TCP", quoted = false)]
+        Tcp,
+        #[at_arg(value = "UDP", quoted = false)]
+        Udp,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct PinStatusResponse {
+        code: PinStatus,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct IpModeResponse {
+        mode: IpMode,
+        id: u8,
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+QIMODE", NoResponse)]
+    struct SetIpMode {
+        mode: IpMode,
+        pin: PinStatus,
+    }
+
+    #[test]
+    fn test_str_enum() {
+        let cmd = SetIpMode {
+            mode: IpMode::Udp,
+            pin: PinStatus::SimPin,
+        };
+        assert_eq!(cmd.as_bytes(), b"AT+QIMODE=UDP,\"SIM PIN\"\r\n"[..]);
+        assert_eq!(<PinStatus as AtatLen>::LEN, 9);
+        assert_eq!(<IpMode as AtatLen>::LEN, 3);
+
+        assert_eq!(
+            from_str::<PinStatusResponse>("+CPIN: SIM PIN"),
+            Ok(PinStatusResponse {
+                code: PinStatus::SimPin
+            })
+        );
+        assert_eq!(
+            from_str::<PinStatusResponse>("+CPIN: \"READY\""),
+            Ok(PinStatusResponse {
+                code: PinStatus::Ready
+            })
+        );
+        assert_eq!(
+            from_str::<IpModeResponse>("+QIMODE: TCP,2"),
+            Ok(IpModeResponse {
+                mode: IpMode::Tcp,
+                id: 2
+            })
+        );
+        assert!(from_str::<PinStatusResponse>("+CPIN: PH-SIM PIN").is_err());
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+USOWR", NoResponse)]
+    struct WriteSocketData {
+        socket: u8,
+        data: serde_at::HexBytes<Vec<u8, 16>>,
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+USECMNG", NoResponse)]
+    struct ImportCertificate<'a> {
+        #[at_arg(len = 8)]
+        name: &'a str,
+        #[at_arg(len = 10)]
+        data: serde_at::Base64Bytes<&'a [u8]>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct SocketData {
+        socket: u8,
+        data: serde_at::Base64Bytes<Vec<u8, 16>>,
+    }
+
+    #[test]
+    fn test_binary_encoding() {
+        let cmd = WriteSocketData {
+            socket: 3,
+            data: serde_at::HexBytes(Vec::from_slice(b"\x01\xABHi").unwrap()),
+        };
+        assert_eq!(cmd.as_bytes(), b"AT+USOWR=3,\"01AB4869\"\r\n"[..]);
+        assert_eq!(
+            <serde_at::HexBytes<Vec<u8, 16>> as AtatLen>::LEN,
+            16 * 2 + 2
+        );
+        assert_eq!(<serde_at::Base64Bytes<Vec<u8, 16>> as AtatLen>::LEN, 24 + 2);
+        assert_eq!(<serde_at::Base64Bytes<Vec<u8, 3>> as AtatLen>::LEN, 4 + 2);
+
+        let cmd = ImportCertificate {
+            name: "ca",
+            data: serde_at::Base64Bytes(b"cert"),
+        };
+        assert_eq!(cmd.as_bytes(), b"AT+USECMNG=\"ca\",\"Y2VydA==\"\r\n"[..]);
+
+        assert_eq!(
+            from_str::<SocketData>("+USORD: 3,\"Y2VydA==\""),
+            Ok(SocketData {
+                socket: 3,
+                data: serde_at::Base64Bytes(Vec::from_slice(b"cert").unwrap()),
+            })
+        );
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct Everything {
+        a: Option<u8>,
+        b: i64,
+        c: bool,
+        d: f32,
+        e: String<16>,
+        f: Vec<u8, 4>,
+        g: serde_at::ValueRange<i16>,
+        h: serde_at::CharVec<8>,
+        i: (u8, i8),
+        j: SimpleEnumU32,
+    }
+
+    /// Feeds valid responses, mutations of them, and random garbage through
+    /// `from_slice` for every derive shape. Any input must result in either a
+    /// value or an error, never a panic. Coverage guided fuzzing lives in
+    /// `fuzz/`.
+    #[test]
+    fn fuzz_from_slice() {
+        const ALPHABET: &[u8] = b"+:,\"()-.eE0123456789 \r\nabcxyzTRUEtruefalse\xff\x00";
+        const SEEDS: &[&[u8]] = &[
+            b"+CFUN: 4",
+            b"+UTEST: 21,99",
+            b"+UTEST: (21,99)",
+            b"",
+            b"2,251,\"deser\",-43,2",
+            b"4,77,\"whaat\",88,1",
+            b"+CMD: 1,-2,true,1.5e3,\"str\",(1,2,3),(0-5),IMP,(4,-5),2",
+        ];
+
+        // xorshift32, for a deterministic sequence of inputs
+        let mut state = 0x1234_5678_u32;
+        let mut rand = move |max: usize| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize % max
+        };
+
+        macro_rules! parse_all {
+            ($input:expr) => {{
+                let input: &[u8] = $input;
+                let _ = from_slice::<NoResponse>(input);
+                let _ = from_slice::<Functionality>(input);
+                let _ = from_slice::<SignalQuality>(input);
+                let _ = from_slice::<Rssi>(input);
+                let _ = from_slice::<SimpleEnum>(input);
+                let _ = from_slice::<SimpleEnumU32>(input);
+                let _ = from_slice::<MixedEnum<'_>>(input);
+                let _ = from_slice::<Everything>(input);
+            }};
+        }
+
+        assert!(from_slice::<Everything>(SEEDS[6]).is_ok());
+
+        for seed in SEEDS {
+            parse_all!(seed);
+
+            for _ in 0..500 {
+                let mut input: Vec<u8, 128> = Vec::from_slice(seed).unwrap();
+                for _ in 0..=rand(4) {
+                    let c = ALPHABET[rand(ALPHABET.len())];
+                    match rand(4) {
+                        0 if !input.is_empty() => {
+                            let i = rand(input.len());
+                            input[i] = c;
+                        }
+                        1 if !input.is_empty() => {
+                            let len = rand(input.len());
+                            input.truncate(len);
+                        }
+                        2 if !input.is_empty() => {
+                            let i = rand(input.len());
+                            let tail: Vec<u8, 128> = Vec::from_slice(&input[i + 1..]).unwrap();
+                            input.truncate(i);
+                            input.extend_from_slice(&tail).unwrap();
+                        }
+                        _ => {
+                            input.push(c).ok();
+                        }
+                    }
+                }
+                parse_all!(&input);
+            }
+        }
+
+        for _ in 0..2000 {
+            let mut input: Vec<u8, 32> = Vec::new();
+            for _ in 0..rand(32) {
+                input.push(ALPHABET[rand(ALPHABET.len())]).unwrap();
+            }
+            parse_all!(&input);
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct PdpAddress {
+        cid: u8,
+        address: String<16>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct Status {
+        #[at_arg(prefix = "+CSQ")]
+        signal: SignalQuality,
+        #[at_arg(prefix = "+CGPADDR")]
+        addresses: Vec<PdpAddress, 2>,
+        #[at_arg(prefix = "+CPIN")]
+        pin: Option<PinStatus>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct Identification {
+        #[at_arg(prefix = "Manufacturer")]
+        manufacturer: String<16>,
+        #[at_arg(prefix = "Model")]
+        model: String<16>,
+    }
+
+    #[test]
+    fn test_prefixed_lines() {
+        assert_eq!(
+            from_str::<Status>(
+                "+CGPADDR: 1,\"10.0.0.1\"\r\n+CSQ: 21,99\r\n+CGPADDR: 2,\"10.0.0.2\"\r\n+CPIN: READY"
+            ),
+            Ok(Status {
+                signal: SignalQuality(21, 99),
+                addresses: Vec::from_slice(&[
+                    PdpAddress {
+                        cid: 1,
+                        address: String::from("10.0.0.1")
+                    },
+                    PdpAddress {
+                        cid: 2,
+                        address: String::from("10.0.0.2")
+                    }
+                ])
+                .unwrap(),
+                pin: Some(PinStatus::Ready),
+            })
+        );
+        // Optional and repeated lines can be missing, and unknown lines are
+        // skipped
+        assert_eq!(
+            from_str::<Status>("+CSQ: 5,0\r\n+COPS: 0,0,\"Op\",7"),
+            Ok(Status {
+                signal: SignalQuality(5, 0),
+                addresses: Vec::new(),
+                pin: None,
+            })
+        );
+        assert_eq!(
+            from_str::<Identification>("Manufacturer: u-blox\r\nModel: SARA-R410M\r\nOK"),
+            Ok(Identification {
+                manufacturer: String::from("u-blox"),
+                model: String::from("SARA-R410M"),
+            })
+        );
+
+        // Missing, duplicate and too many lines
+        assert!(from_str::<Status>("+CPIN: READY").is_err());
+        assert!(from_str::<Status>("+CSQ: 5,0\r\n+CSQ: 6,0").is_err());
+        assert!(from_str::<Status>(
+            "+CSQ: 5,0\r\n+CGPADDR: 1,\"a\"\r\n+CGPADDR: 2,\"b\"\r\n+CGPADDR: 3,\"c\""
+        )
+        .is_err());
+        // Errors are reported within the line
+        let err =
+            serde_at::from_slice_with_context::<Status>(b"+CPIN: READY\r\n+CSQ: 5,x").unwrap_err();
+        assert_eq!(err.index, 22);
+        assert_eq!(err.field, Some("1"));
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    struct FirmwareVersion {
+        #[at_arg(line = 0)]
+        at: String<64>,
+        #[at_arg(line = 2)]
+        compiled: Option<String<64>>,
+    }
+
+    #[derive(Debug, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct Imei {
+        #[at_arg(line = 0)]
+        imei: u64,
+    }
+
+    #[test]
+    fn test_text_lines() {
+        assert_eq!(
+            from_str::<FirmwareVersion>(
+                "AT version:1.1.0.0(May 11 2016 18:09:56)\r\nSDK version:1.5.4(baaeaebb)\r\ncompile time:May 20 2016, 15:08:19\r\nextra"
+            ),
+            Ok(FirmwareVersion {
+                at: String::from("AT version:1.1.0.0(May 11 2016 18:09:56)"),
+                compiled: Some(String::from("compile time:May 20 2016, 15:08:19")),
+            })
+        );
+        assert_eq!(
+            from_str::<FirmwareVersion>("AT version:1.1.0.0\r\nSDK version:1.5.4"),
+            Ok(FirmwareVersion {
+                at: String::from("AT version:1.1.0.0"),
+                compiled: None,
+            })
+        );
+        assert_eq!(
+            from_str::<Imei>("\r\n352913090123456\r\n"),
+            Ok(Imei {
+                imei: 352_913_090_123_456
+            })
+        );
+
+        assert!(from_str::<FirmwareVersion>("").is_err());
+        assert!(from_str::<Imei>("35291309012345x").is_err());
+
+        let mut lines = serde_at::Lines::new(b"Quectel\r\nEC21\r\n\r\nRevision: EC21EFAR06A01M4G ");
+        assert_eq!(lines.next(), Some(&b"Quectel"[..]));
+        assert_eq!(lines.next(), Some(&b"EC21"[..]));
+        assert_eq!(lines.next(), Some(&b"Revision: EC21EFAR06A01M4G"[..]));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[derive(Debug, Clone, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct Manufacturer {
+        id: String<64>,
+    }
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+CGMI", Manufacturer)]
+    struct GetManufacturer;
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+CGPADDR", Vec<PdpAddress, 2>)]
+    struct GetPdpAddresses;
+
+    #[derive(Debug, PartialEq, AtatCmd)]
+    #[at_cmd("+CGPADDR", Vec<PdpAddress, 0>)]
+    struct GetNoPdpAddresses;
+
+    #[test]
+    fn test_resp_len() {
+        use atat::derive::{assert_buf_len, response_len};
+
+        assert_eq!(<Functionality as AtatLen>::LEN, 3);
+        assert_eq!(<PdpAddress as AtatLen>::LEN, 3 + (16 + 2) + 1);
+        // Each line adds its prefix, `: ` and `\r\n`, once per element of a
+        // `Vec`
+        assert_eq!(
+            <Status as AtatLen>::LEN,
+            (4 + 7 + 4) + 2 * (8 + 22 + 4) + (5 + 9 + 4)
+        );
+        assert_eq!(
+            <Identification as AtatLen>::LEN,
+            (12 + 18 + 4) + (5 + 18 + 4)
+        );
+        assert_eq!(<Imei as AtatLen>::LEN, 20 + 2);
+
+        // `+CFUN: `, the response and `\r\nOK\r\n`
+        assert_eq!(response_len::<GetFunctionality>(), 7 + 3 + 6);
+        assert_eq!(response_len::<ManufacturerId>(), 7 + 6);
+        // `+CGMI: "<64 characters>"\r\nOK\r\n`
+        assert_eq!(response_len::<GetManufacturer>(), 7 + 66 + 6);
+        // Every repeated line has the prefix, eg.
+        // `+CGPADDR: 1,"<16>"\r\n+CGPADDR: 2,"<16>"\r\nOK\r\n`
+        assert_eq!(<Vec<PdpAddress, 2> as AtatLen>::LINES, 2);
+        assert_eq!(response_len::<GetPdpAddresses>(), 2 * (10 + 22) + 2 + 6);
+        // Only `\r\nOK\r\n` without any line
+        assert_eq!(response_len::<GetNoPdpAddresses>(), 6);
+
+        // Checked at compile time, error responses need 85 bytes
+        const _: () = assert_buf_len::<GetFunctionality, 85>();
+        const _: () = assert_buf_len::<ManufacturerId, 85>();
+    }
+
+    #[derive(Debug, Clone, PartialEq, AtatResp)]
+    #[at_resp(len)]
+    struct ReceivedData<'a> {
+        #[at_arg(position = 0)]
+        id: u8,
+        #[at_arg(position = 1, len = 16)]
+        data: &'a str,
+    }
+
+    #[derive(Debug, Clone, PartialEq, AtatCmd)]
+    #[at_cmd("+URD", ReceivedData<'a>)]
+    struct ReadData {
+        id: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, AtatCmd)]
+    #[at_cmd("+UECHO", ReceivedData<'b>)]
+    struct EchoData<'a> {
+        id: u8,
+        #[at_arg(len = 16)]
+        data: &'a str,
+    }
+
+    #[test]
+    fn test_borrowed_response() {
+        use atat::derive::response_len;
+
+        let buf = b"+URD: 1,\"hello\"";
+        let resp = ReadData { id: 1 }.parse(Ok(buf)).unwrap();
+        assert_eq!(
+            resp,
+            ReceivedData {
+                id: 1,
+                data: "hello"
+            }
+        );
+        assert_eq!(resp.data.as_ptr(), buf[9..].as_ptr());
+
+        // The command and the response borrow with different lifetimes
+        let data = String::<16>::from("hello");
+        let cmd = EchoData { id: 1, data: &data };
+        assert_eq!(
+            cmd.parse(Ok(b"+UECHO: 1,\"hello\"")),
+            Ok(ReceivedData {
+                id: 1,
+                data: "hello"
+            })
+        );
+
+        assert_eq!(response_len::<ReadData>(), 6 + 3 + 1 + 16 + 6);
+    }
+
+    #[derive(Debug, Clone, PartialEq, AtatCmd)]
+    #[at_cmd("+UWAPC", NoResponse)]
+    struct ConfigureAccessPoint<'a> {
+        #[at_arg(range = "0..=4")]
+        id: u8,
+        #[at_arg(len = 32, max_len = 32, charset = "[A-Za-z0-9_\\-]")]
+        ssid: &'a str,
+        #[at_arg(max_len = 8, charset = "[^\",]")]
+        password: String<16>,
+        #[at_arg(range = "1..=13")]
+        channel: Option<u8>,
+    }
+
+    #[test]
+    fn test_validation() {
+        let valid = ConfigureAccessPoint {
+            id: 4,
+            ssid: "my_AP-1",
+            password: String::from("p@ss w0"),
+            channel: None,
+        };
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(
+            ConfigureAccessPoint {
+                channel: Some(13),
+                ..valid.clone()
+            }
+            .validate(),
+            Ok(())
+        );
+        // Validation doesn't affect serialization
+        assert_eq!(
+            valid.as_bytes(),
+            b"AT+UWAPC=4,\"my_AP-1\",\"p@ss w0\"\r\n"[..]
+        );
+
+        let invalid = [
+            (
+                ConfigureAccessPoint {
+                    id: 5,
+                    ..valid.clone()
+                },
+                "id",
+            ),
+            (
+                ConfigureAccessPoint {
+                    ssid: "my AP",
+                    ..valid.clone()
+                },
+                "ssid",
+            ),
+            (
+                ConfigureAccessPoint {
+                    ssid: "012345678901234567890123456789012",
+                    ..valid.clone()
+                },
+                "ssid",
+            ),
+            (
+                ConfigureAccessPoint {
+                    password: String::from("a\"b"),
+                    ..valid.clone()
+                },
+                "password",
+            ),
+            (
+                ConfigureAccessPoint {
+                    password: String::from("012345678"),
+                    ..valid.clone()
+                },
+                "password",
+            ),
+            (
+                ConfigureAccessPoint {
+                    channel: Some(0),
+                    ..valid.clone()
+                },
+                "channel",
+            ),
+        ];
+        for (cmd, field) in invalid.iter() {
+            assert_eq!(cmd.validate(), Err(atat::Error::InvalidArgument(field)));
+        }
     }
 
     #[test]
     fn test_atat_len() {
-        assert_eq!(<char as AtatLen>::Len::to_usize(), 1);
-        assert_eq!(<bool as AtatLen>::Len::to_usize(), 5);
-        assert_eq!(<isize as AtatLen>::Len::to_usize(), 19);
-        assert_eq!(<usize as AtatLen>::Len::to_usize(), 20);
-        assert_eq!(<u8 as AtatLen>::Len::to_usize(), 3);
-        assert_eq!(<u16 as AtatLen>::Len::to_usize(), 5);
-        assert_eq!(<u32 as AtatLen>::Len::to_usize(), 10);
-        assert_eq!(<u64 as AtatLen>::Len::to_usize(), 20);
-        assert_eq!(<u128 as AtatLen>::Len::to_usize(), 39);
-        assert_eq!(<i8 as AtatLen>::Len::to_usize(), 4);
-        assert_eq!(<i16 as AtatLen>::Len::to_usize(), 6);
-        assert_eq!(<i32 as AtatLen>::Len::to_usize(), 11);
-        assert_eq!(<i64 as AtatLen>::Len::to_usize(), 20);
-        assert_eq!(<i128 as AtatLen>::Len::to_usize(), 40);
-        assert_eq!(<f32 as AtatLen>::Len::to_usize(), 42);
-        assert_eq!(<f64 as AtatLen>::Len::to_usize(), 312);
+        assert_eq!(<char as AtatLen>::LEN, 1);
+        assert_eq!(<bool as AtatLen>::LEN, 5);
+        assert_eq!(<isize as AtatLen>::LEN, 19);
+        assert_eq!(<usize as AtatLen>::LEN, 20);
+        assert_eq!(<u8 as AtatLen>::LEN, 3);
+        assert_eq!(<u16 as AtatLen>::LEN, 5);
+        assert_eq!(<u32 as AtatLen>::LEN, 10);
+        assert_eq!(<u64 as AtatLen>::LEN, 20);
+        assert_eq!(<u128 as AtatLen>::LEN, 39);
+        assert_eq!(<i8 as AtatLen>::LEN, 4);
+        assert_eq!(<i16 as AtatLen>::LEN, 6);
+        assert_eq!(<i32 as AtatLen>::LEN, 11);
+        assert_eq!(<i64 as AtatLen>::LEN, 20);
+        assert_eq!(<i128 as AtatLen>::LEN, 40);
+        assert_eq!(<f32 as AtatLen>::LEN, 48);
+        assert_eq!(<f64 as AtatLen>::LEN, 327);
+        assert_eq!(<() as AtatLen>::LEN, 0);
+        assert_eq!(<(u8,) as AtatLen>::LEN, 3);
+        assert_eq!(<(u8, bool) as AtatLen>::LEN, 9);
+        assert_eq!(<(u8, bool, i16) as AtatLen>::LEN, 16);
 
-        assert_eq!(<SimpleEnum as AtatLen>::Len::to_usize(), 3);
-        assert_eq!(<SimpleEnumU32 as AtatLen>::Len::to_usize(), 10);
-        // (fields) + (n_fields - 1)
-        // (3 + 128 + 2 + 150 + 3 + 10 + 3 + (10*5)) + 7
+        assert_eq!(<SimpleEnum as AtatLen>::LEN, 3);
+        assert_eq!(<SimpleEnumU32 as AtatLen>::LEN, 10);
+        // (fields) + (n_fields - 1), strings include their quotes
+        // (3 + (128 + 2) + 2 + 150 + 3 + 10 + 3 + (10*5)) + 7
         assert_eq!(
-            <LengthTester<'_> as AtatLen>::Len::to_usize(),
-            (3 + 128 + 2 + 150 + 3 + 10 + 3) + 6
+            <LengthTester<'_> as AtatLen>::LEN,
+            (3 + (128 + 2) + 2 + 150 + 3 + 10 + 3) + 6
         );
         assert_eq!(
-            <MixedEnum<'_> as AtatLen>::Len::to_usize(),
-            (3 + 3 + 10 + 20 + 10) + 4
+            <MixedEnum<'_> as AtatLen>::LEN,
+            (3 + 3 + (10 + 2) + 20 + 10) + 4
         );
     }
 
@@ -181,52 +1282,51 @@ mod tests {
                 // d: Vec::new()
             }
             .as_bytes(),
-            Vec::<u8, consts::U360>::from_slice(b"AT+CFUN=8,\"SomeString\",2,\"whatup\",0,0,1\r\n")
-                .unwrap()
+            Vec::<u8, 360>::from_slice(b"AT+CFUN=8,\"SomeString\",2,\"whatup\",0,0,1\r\n").unwrap()
         );
     }
 
     #[test]
     fn test_mixed_enum() {
         assert_eq!(
-            to_string::<consts::U1, consts::U3, _>(
+            to_string::<1, 3, _>(
                 &MixedEnum::UnitVariant,
                 String::from("CMD"),
                 SerializeOptions::default()
             )
             .unwrap(),
-            String::<consts::U1>::from("0")
+            String::<1>::from("0")
         );
         assert_eq!(
-            to_string::<consts::U10, consts::U3, _>(
+            to_string::<10, 3, _>(
                 &MixedEnum::SingleSimpleTuple(15),
                 String::from("CMD"),
                 SerializeOptions::default()
             )
             .unwrap(),
-            String::<consts::U10>::from("1,15")
+            String::<10>::from("1,15")
         );
         assert_eq!(
-            to_string::<consts::U50, consts::U3, _>(
+            to_string::<50, 3, _>(
                 &MixedEnum::AdvancedTuple(25, String::from("testing"), -54, SimpleEnumU32::A),
                 String::from("CMD"),
                 SerializeOptions::default()
             )
             .unwrap(),
-            String::<consts::U50>::from("2,25,\"testing\",-54,0")
+            String::<50>::from("2,25,\"testing\",-54,0")
         );
         assert_eq!(
-            to_string::<consts::U10, consts::U3, _>(
+            to_string::<10, 3, _>(
                 &MixedEnum::SingleSimpleStruct { x: 35 },
                 String::from("CMD"),
                 SerializeOptions::default()
             )
             .unwrap(),
-            String::<consts::U10>::from("3,35")
+            String::<10>::from("3,35")
         );
 
         assert_eq!(
-            to_string::<consts::U50, consts::U3, _>(
+            to_string::<50, 3, _>(
                 &MixedEnum::AdvancedStruct {
                     a: 77,
                     b: String::from("whaat"),
@@ -237,7 +1337,7 @@ mod tests {
                 SerializeOptions::default()
             )
             .unwrap(),
-            String::<consts::U50>::from("4,77,\"whaat\",88,1")
+            String::<50>::from("4,77,\"whaat\",88,1")
         );
 
         assert_eq!(Ok(MixedEnum::UnitVariant), from_str::<MixedEnum<'_>>("0"));
@@ -3,7 +3,14 @@ use crate::{
     urc_matcher::{UrcMatcher, UrcMatcherResult},
     InternalError,
 };
-use heapless::{ArrayLength, Vec};
+use heapless::Vec;
+
+/// Final result code of a successful response
+const OK: &[u8] = b"OK";
+
+/// Length of the final result code following a response in the buffer,
+/// `\r\nOK\r\n`
+pub const OK_LEN: usize = OK.len() + 4;
 
 pub trait Digester {
     /// Command line termination character S3 (Default = b'\r' ASCII: \[013\])
@@ -16,7 +23,16 @@ pub trait Digester {
 
     fn force_receive_state(&mut self);
 
-    fn digest<L: ArrayLength<u8>>(
+    /// Whether the digester is currently waiting for the response to a
+    /// command, in which case the client is notified when a partially
+    /// received response is discarded.
+    ///
+    /// Defaults to `false`, leaving the client to time out on its own.
+    fn is_receiving_response(&self) -> bool {
+        false
+    }
+
+    fn digest<const L: usize>(
         &mut self,
         buf: &mut Vec<u8, L>,
         urc_matcher: &mut impl UrcMatcher,
@@ -24,7 +40,7 @@ pub trait Digester {
 }
 
 #[derive(Debug, PartialEq)]
-pub enum DigestResult<L: ArrayLength<u8>> {
+pub enum DigestResult<const L: usize> {
     Urc(Vec<u8, L>),
     Response(Result<Vec<u8, L>, InternalError>),
     None,
@@ -68,8 +84,12 @@ impl Digester for DefaultDigester {
         self.state = State::ReceivingResponse;
     }
 
+    fn is_receiving_response(&self) -> bool {
+        self.state == State::ReceivingResponse
+    }
+
     #[allow(clippy::cognitive_complexity)]
-    fn digest<L: ArrayLength<u8>>(
+    fn digest<const L: usize>(
         &mut self,
         buf: &mut Vec<u8, L>,
         urc_matcher: &mut impl UrcMatcher,
@@ -177,7 +197,7 @@ impl Digester for DefaultDigester {
             State::ReceivingResponse => {
                 let resp = if let Some(mut line) = get_line::<L, _>(
                     buf,
-                    b"OK",
+                    OK,
                     Self::LINE_TERM_CHAR,
                     Self::FORMAT_CHAR,
                     true,
@@ -258,15 +278,15 @@ mod test {
     use crate::queues::{ComQueue, ResQueue, UrcQueue};
     use crate::urc_matcher::{DefaultUrcMatcher, UrcMatcherResult};
     use crate::{digest::State, urc_matcher};
-    use heapless::{consts, spsc::Queue};
+    use heapless::spsc::Queue;
 
-    type TestRxBufLen = consts::U256;
+    const TEST_RX_BUF_LEN: usize = 256;
 
     #[test]
     fn no_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT\r\r\n\r\n").unwrap();
@@ -289,7 +309,7 @@ mod test {
     fn response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+USORD=3,16\r\n").unwrap();
@@ -308,24 +328,26 @@ mod test {
 
         {
             let expectation =
-                Vec::<_, TestRxBufLen>::from_slice(b"+USORD: 3,16,\"16 bytes of data\"\r\n")
+                Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+USORD: 3,16,\"16 bytes of data\"\r\n")
                     .unwrap();
             assert_eq!(buf, expectation);
         }
 
         buf.extend_from_slice(b"OK\r\n").unwrap();
         {
-            let expectation =
-                Vec::<_, TestRxBufLen>::from_slice(b"+USORD: 3,16,\"16 bytes of data\"\r\nOK\r\n")
-                    .unwrap();
+            let expectation = Vec::<_, TEST_RX_BUF_LEN>::from_slice(
+                b"+USORD: 3,16,\"16 bytes of data\"\r\nOK\r\n",
+            )
+            .unwrap();
             assert_eq!(buf, expectation);
         }
         let result = digester.digest(&mut buf, &mut urc_matcher);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(digester.state, State::Idle);
         {
             let expectation =
-                Vec::<_, TestRxBufLen>::from_slice(b"+USORD: 3,16,\"16 bytes of data\"").unwrap();
+                Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+USORD: 3,16,\"16 bytes of data\"")
+                    .unwrap();
             assert_eq!(result, DigestResult::Response(Ok(expectation)));
         }
     }
@@ -334,7 +356,7 @@ mod test {
     fn multi_line_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+GMR\r\r\n").unwrap();
@@ -347,10 +369,10 @@ mod test {
         buf.extend_from_slice(b"AT version:1.1.0.0(May 11 2016 18:09:56)\r\nSDK version:1.5.4(baaeaebb)\r\ncompile time:May 20 2016 15:08:19\r\nOK\r\n").unwrap();
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(digester.state, State::Idle);
         {
-            let expectation = Vec::<_, TestRxBufLen>::from_slice(b"AT version:1.1.0.0(May 11 2016 18:09:56)\r\nSDK version:1.5.4(baaeaebb)\r\ncompile time:May 20 2016 15:08:19").unwrap();
+            let expectation = Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"AT version:1.1.0.0(May 11 2016 18:09:56)\r\nSDK version:1.5.4(baaeaebb)\r\ncompile time:May 20 2016 15:08:19").unwrap();
             assert_eq!(result, DigestResult::Response(Ok(expectation)));
         }
     }
@@ -359,18 +381,19 @@ mod test {
     fn urc() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
 
         buf.extend_from_slice(b"+UUSORD: 3,16,\"16 bytes of data\"\r\n")
             .unwrap();
         let result = digester.digest(&mut buf, &mut urc_matcher);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(digester.state, State::Idle);
         {
             let expectation =
-                Vec::<_, TestRxBufLen>::from_slice(b"+UUSORD: 3,16,\"16 bytes of data\"").unwrap();
+                Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+UUSORD: 3,16,\"16 bytes of data\"")
+                    .unwrap();
             assert_eq!(result, DigestResult::Urc(expectation));
         }
     }
@@ -379,10 +402,10 @@ mod test {
     fn read_error() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
 
         buf.extend_from_slice(b"OK\r\n").unwrap();
         assert_eq!(
@@ -397,7 +420,7 @@ mod test {
     fn error_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+USORD=3,16\r\n").unwrap();
@@ -419,7 +442,7 @@ mod test {
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(
             result,
             DigestResult::Response(Err(InternalError::Error(
@@ -436,7 +459,7 @@ mod test {
     fn chunkwise_digest() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
 
@@ -462,7 +485,7 @@ mod test {
     fn bytewise_digest() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
 
@@ -482,7 +505,7 @@ mod test {
     fn invalid_line_with_termination() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
 
@@ -507,7 +530,7 @@ mod test {
     fn mixed_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
 
@@ -528,12 +551,12 @@ mod test {
     fn clear_buf_complete() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         buf.extend_from_slice(b"hello\r\ngoodbye\r\n").unwrap();
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"hello\r\ngoodbye\r\n").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"hello\r\ngoodbye\r\n").unwrap()
         );
 
         assert_eq!(
@@ -544,20 +567,20 @@ mod test {
             digester.digest(&mut buf, &mut urc_matcher),
             DigestResult::None
         );
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::from_slice(b"").unwrap());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"").unwrap());
     }
 
     #[test]
     fn clear_buf_partial() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         buf.extend_from_slice(b"hello\r\nthere\r\ngoodbye\r\n")
             .unwrap();
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"hello\r\nthere\r\ngoodbye\r\n").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"hello\r\nthere\r\ngoodbye\r\n").unwrap()
         );
 
         assert_eq!(
@@ -567,7 +590,7 @@ mod test {
 
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"there\r\ngoodbye\r\n").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"there\r\ngoodbye\r\n").unwrap()
         );
 
         assert_eq!(
@@ -577,27 +600,27 @@ mod test {
 
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"goodbye\r\n").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"goodbye\r\n").unwrap()
         );
 
         assert_eq!(
             digester.digest(&mut buf, &mut urc_matcher),
             DigestResult::None
         );
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::from_slice(b"").unwrap());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"").unwrap());
     }
 
     #[test]
     fn clear_buf_partial_no_newlines() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         buf.extend_from_slice(b"no newlines anywhere").unwrap();
 
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"no newlines anywhere").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"no newlines anywhere").unwrap()
         );
 
         assert_eq!(
@@ -605,14 +628,14 @@ mod test {
             DigestResult::None
         );
 
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::from_slice(b"").unwrap());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"").unwrap());
     }
 
     #[test]
     fn custom_urc_matcher() {
         struct MyUrcMatcher {}
         impl UrcMatcher for MyUrcMatcher {
-            fn process<L: ArrayLength<u8>>(&mut self, buf: &mut Vec<u8, L>) -> UrcMatcherResult<L> {
+            fn process<const L: usize>(&mut self, buf: &mut Vec<u8, L>) -> UrcMatcherResult<L> {
                 if buf.len() >= 6 && buf.get(0..6) == Some(b"+match") {
                     let data = buf.clone();
                     buf.truncate(0);
@@ -627,7 +650,7 @@ mod test {
 
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = MyUrcMatcher {};
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         // Initial state
         assert_eq!(digester.state, State::Idle);
@@ -639,7 +662,7 @@ mod test {
         assert_eq!(digester.state, State::Idle);
         assert_eq!(
             result,
-            DigestResult::Urc(Vec::<_, TestRxBufLen>::from_slice(b"+default-behavior").unwrap())
+            DigestResult::Urc(Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+default-behavior").unwrap())
         );
 
         // Check an URC that is generally handled by MyUrcMatcher but
@@ -656,7 +679,7 @@ mod test {
         assert_eq!(digester.state, State::Idle);
         assert_eq!(
             result,
-            DigestResult::Urc(Vec::<_, TestRxBufLen>::from_slice(b"+match").unwrap())
+            DigestResult::Urc(Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+match").unwrap())
         );
     }
 
@@ -664,7 +687,7 @@ mod test {
     fn numeric_error_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+USORD=3,16\r\n").unwrap();
@@ -686,7 +709,7 @@ mod test {
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(
             result,
             DigestResult::Response(Err(InternalError::Error(
@@ -699,7 +722,7 @@ mod test {
     fn verbose_error_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+USORD=3,16\r\n").unwrap();
@@ -722,7 +745,7 @@ mod test {
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(
             result,
             DigestResult::Response(Err(InternalError::Error(
@@ -735,7 +758,7 @@ mod test {
     fn truncate_verbose_error_response() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+USORD=3,16\r\n").unwrap();
@@ -757,7 +780,7 @@ mod test {
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(
             result,
             DigestResult::Response(Err(InternalError::Error(
@@ -773,7 +796,7 @@ mod test {
     fn data_ready_prompt() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+USECMNG=0,0,\"Verisign\",1758\r>")
@@ -787,7 +810,7 @@ mod test {
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(result, DigestResult::Response(Ok(heapless::Vec::new())));
     }
 
@@ -796,7 +819,7 @@ mod test {
     fn cpin_parsing() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+CPIN?\r\r\n+CPIN: READY\r\n\r\nOK\r\n")
@@ -809,13 +832,13 @@ mod test {
         assert_eq!(digester.state, State::ReceivingResponse);
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"+CPIN: READY\r\n\r\nOK\r\n").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+CPIN: READY\r\n\r\nOK\r\n").unwrap()
         );
 
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(
             result,
             DigestResult::Response(Ok(Vec::from_slice(b"+CPIN: READY").unwrap()))
@@ -827,7 +850,7 @@ mod test {
     fn cpin_error() {
         let mut digester = DefaultDigester::default();
         let mut urc_matcher = DefaultUrcMatcher::default();
-        let mut buf = Vec::<u8, TestRxBufLen>::new();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::new();
 
         assert_eq!(digester.state, State::Idle);
         buf.extend_from_slice(b"AT+CPIN?\r\r\n+CME ERROR: 10\r\n")
@@ -840,13 +863,13 @@ mod test {
         assert_eq!(digester.state, State::ReceivingResponse);
         assert_eq!(
             buf,
-            Vec::<_, TestRxBufLen>::from_slice(b"+CME ERROR: 10\r\n").unwrap()
+            Vec::<_, TEST_RX_BUF_LEN>::from_slice(b"+CME ERROR: 10\r\n").unwrap()
         );
 
         let result = digester.digest(&mut buf, &mut urc_matcher);
 
         assert_eq!(digester.state, State::Idle);
-        assert_eq!(buf, Vec::<_, TestRxBufLen>::new());
+        assert_eq!(buf, Vec::<_, TEST_RX_BUF_LEN>::new());
         assert_eq!(
             result,
             DigestResult::Response(Err(InternalError::Error(
@@ -854,4 +877,35 @@ mod test {
             )))
         );
     }
+    /// Digester implementing only the required methods
+    struct LineDigester;
+
+    impl Digester for LineDigester {
+        fn reset(&mut self) {}
+
+        fn force_receive_state(&mut self) {}
+
+        fn digest<const L: usize>(
+            &mut self,
+            buf: &mut Vec<u8, L>,
+            _urc_matcher: &mut impl UrcMatcher,
+        ) -> DigestResult<L> {
+            let resp = Vec::from_slice(buf).unwrap_or_default();
+            buf.clear();
+            DigestResult::Response(Ok(resp))
+        }
+    }
+
+    #[test]
+    fn custom_digester() {
+        let mut digester = LineDigester;
+        let mut urc_matcher = DefaultUrcMatcher::default();
+        let mut buf = Vec::<u8, TEST_RX_BUF_LEN>::from_slice(b"OK").unwrap();
+
+        assert!(!digester.is_receiving_response());
+        assert_eq!(
+            digester.digest(&mut buf, &mut urc_matcher),
+            DigestResult::Response(Ok(Vec::from_slice(b"OK").unwrap()))
+        );
+    }
 }
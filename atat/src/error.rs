@@ -1,4 +1,8 @@
-use heapless::{consts, String, Vec};
+use heapless::Vec;
+
+/// Maximum length of the message of an error response, kept in
+/// [`InternalError::Error`]
+pub const ERROR_LEN: usize = 85;
 
 /// Errors returned used internally within the crate
 #[derive(Clone, Debug, PartialEq)]
@@ -18,7 +22,7 @@ pub enum InternalError {
     /// Failed to parse received response
     Parse,
     /// Error response containing any error message
-    Error(Vec<u8, consts::U85>),
+    Error(Vec<u8, ERROR_LEN>),
 }
 
 impl defmt::Format for InternalError {
@@ -55,11 +59,29 @@ where
     /// Buffer overflow
     Overflow,
     /// Failed to parse received response
-    Parse,
+    Parse(ParseError),
+    /// Failed to serialize the command
+    Serialize,
+    /// A command argument is outside its allowed range, length or charset.
+    /// Contains the name of the field.
+    InvalidArgument(&'static str),
     /// Error response containing any error message
     Error(E),
 }
 
+impl<E> From<serde_at::ser::Error> for Error<E>
+where
+    E: defmt::Format,
+{
+    fn from(e: serde_at::ser::Error) -> Self {
+        match e {
+            serde_at::ser::Error::BufferFull => Self::Overflow,
+            serde_at::ser::Error::Write => Self::Write,
+            _ => Self::Serialize,
+        }
+    }
+}
+
 impl<E> From<&InternalError> for Error<E>
 where
     E: core::str::FromStr + defmt::Format,
@@ -72,15 +94,105 @@ where
             InternalError::InvalidResponse => Self::InvalidResponse,
             InternalError::Aborted => Self::Aborted,
             InternalError::Overflow => Self::Overflow,
-            InternalError::Parse => Self::Parse,
+            InternalError::Parse => Self::Parse(ParseError::default()),
             InternalError::Error(ref e) => {
-                if let Ok(s) = String::from_utf8(e.clone()) {
-                    if let Ok(e) = core::str::FromStr::from_str(s.as_str()) {
+                if let Ok(s) = core::str::from_utf8(e) {
+                    if let Ok(e) = core::str::FromStr::from_str(s) {
                         return Self::Error(e);
                     }
                 }
-                Self::Parse
+                Self::Parse(ParseError::default())
+            }
+        }
+    }
+}
+
+/// Kind and location of a response parse failure.
+///
+/// The kind and location are only tracked when the `parse-error-context`
+/// feature is enabled, as it increases the size of [`Error`]. Otherwise all
+/// accessors return `None`.
+///
+/// [`Error`]: enum.Error.html
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    #[cfg(feature = "parse-error-context")]
+    kind: Option<serde_at::de::Error>,
+    #[cfg(feature = "parse-error-context")]
+    index: usize,
+    #[cfg(feature = "parse-error-context")]
+    field: Option<&'static str>,
+}
+
+impl ParseError {
+    /// The deserializer error, if parsing failed in the deserializer rather
+    /// than eg. on an unexpected error response
+    pub fn kind(&self) -> Option<&serde_at::de::Error> {
+        #[cfg(feature = "parse-error-context")]
+        return self.kind.as_ref();
+        #[cfg(not(feature = "parse-error-context"))]
+        return None;
+    }
+
+    /// Byte offset into the response at which parsing failed
+    pub fn index(&self) -> Option<usize> {
+        #[cfg(feature = "parse-error-context")]
+        return Some(self.index);
+        #[cfg(not(feature = "parse-error-context"))]
+        return None;
+    }
+
+    /// Name of the response field being parsed when parsing failed, if any.
+    /// Fields of tuple structs are named by their index, eg. `"0"`.
+    pub fn field(&self) -> Option<&'static str> {
+        #[cfg(feature = "parse-error-context")]
+        return self.field;
+        #[cfg(not(feature = "parse-error-context"))]
+        return None;
+    }
+}
+
+impl From<serde_at::de::ErrorContext> for ParseError {
+    #[cfg_attr(not(feature = "parse-error-context"), allow(unused_variables))]
+    fn from(e: serde_at::de::ErrorContext) -> Self {
+        Self {
+            #[cfg(feature = "parse-error-context")]
+            kind: Some(e.error),
+            #[cfg(feature = "parse-error-context")]
+            index: e.index,
+            #[cfg(feature = "parse-error-context")]
+            field: e.field,
+        }
+    }
+}
+
+impl defmt::Format for ParseError {
+    fn format(&self, f: defmt::Formatter) {
+        #[cfg(feature = "parse-error-context")]
+        if let Some(kind) = &self.kind {
+            let kind = defmt::Debug2Format(kind);
+            return match self.field {
+                Some(field) => defmt::write!(
+                    f,
+                    "ParseError({:?}, index: {=usize}, field: {=str})",
+                    kind,
+                    self.index,
+                    field
+                ),
+                None => defmt::write!(f, "ParseError({:?}, index: {=usize})", kind, self.index),
+            };
+        }
+        match (self.index(), self.field()) {
+            (Some(index), Some(field)) => {
+                defmt::write!(
+                    f,
+                    "ParseError(index: {=usize}, field: {=str})",
+                    index,
+                    field
+                )
             }
+            (Some(index), None) => defmt::write!(f, "ParseError(index: {=usize})", index),
+            _ => defmt::write!(f, "ParseError"),
         }
     }
 }
@@ -1,31 +1,31 @@
-use heapless::{consts, ArrayLength, Vec};
+use heapless::Vec;
 
 use crate::error::InternalError;
-use crate::queues::{ComConsumer, ResProducer, UrcItem, UrcProducer};
-use crate::Command;
+use crate::queues::{ComConsumer, ResProducer, UrcProducer, UrcQueueLen};
 use crate::{
     digest::{DefaultDigester, DigestResult, Digester},
     urc_matcher::{DefaultUrcMatcher, UrcMatcher},
 };
+use crate::{Clock, Command, Config, NoClock};
 
 pub struct IngressManager<
-    BufLen = consts::U256,
+    const BUF_LEN: usize = 256,
     D = DefaultDigester,
     U = DefaultUrcMatcher,
-    UrcCapacity = consts::U10,
+    const URC_QUEUE_LEN: usize = 10,
+    C = NoClock,
 > where
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
     U: UrcMatcher,
     D: Digester,
+    C: Clock,
 {
     /// Buffer holding incoming bytes.
-    buf: Vec<u8, BufLen>,
+    buf: Vec<u8, BUF_LEN>,
 
     /// The response producer sends responses to the client
-    res_p: ResProducer<BufLen>,
+    res_p: ResProducer<BUF_LEN>,
     /// The URC producer sends URCs to the client
-    urc_p: UrcProducer<BufLen, UrcCapacity>,
+    urc_p: UrcProducer<BUF_LEN, URC_QUEUE_LEN>,
     /// The command consumer receives commands from the client
     com_c: ComConsumer,
 
@@ -34,17 +34,21 @@ pub struct IngressManager<
 
     /// URC matcher.
     urc_matcher: U,
+
+    /// Clock used for the inter-character timeout
+    clock: C,
+    config: Config,
+    /// Instant of the last call to `write`
+    last_write: u32,
 }
 
-impl<BufLen, UrcCapacity> IngressManager<BufLen, DefaultDigester, DefaultUrcMatcher, UrcCapacity>
-where
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+impl<const BUF_LEN: usize, const URC_QUEUE_LEN: usize>
+    IngressManager<BUF_LEN, DefaultDigester, DefaultUrcMatcher, URC_QUEUE_LEN, NoClock>
 {
     #[must_use]
     pub fn new(
-        res_p: ResProducer<BufLen>,
-        urc_p: UrcProducer<BufLen, UrcCapacity>,
+        res_p: ResProducer<BUF_LEN>,
+        urc_p: UrcProducer<BUF_LEN, URC_QUEUE_LEN>,
         com_c: ComConsumer,
     ) -> Self {
         Self::with_customs(
@@ -53,24 +57,30 @@ where
             com_c,
             DefaultUrcMatcher::default(),
             DefaultDigester::default(),
+            NoClock,
+            Config::default(),
         )
     }
 }
 
-impl<BufLen, U, D, UrcCapacity> IngressManager<BufLen, D, U, UrcCapacity>
+impl<const BUF_LEN: usize, U, D, const URC_QUEUE_LEN: usize, C>
+    IngressManager<BUF_LEN, D, U, URC_QUEUE_LEN, C>
 where
     D: Digester,
     U: UrcMatcher,
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
+    C: Clock,
 {
     pub fn with_customs(
-        res_p: ResProducer<BufLen>,
-        urc_p: UrcProducer<BufLen, UrcCapacity>,
+        res_p: ResProducer<BUF_LEN>,
+        urc_p: UrcProducer<BUF_LEN, URC_QUEUE_LEN>,
         com_c: ComConsumer,
         urc_matcher: U,
         digester: D,
+        clock: C,
+        config: Config,
     ) -> Self {
+        let () = UrcQueueLen::<URC_QUEUE_LEN>::ASSERT;
+        let last_write = clock.now_ms();
         Self {
             buf: Vec::new(),
             res_p,
@@ -78,6 +88,9 @@ where
             com_c,
             urc_matcher,
             digester,
+            clock,
+            config,
+            last_write,
         }
     }
 
@@ -89,6 +102,7 @@ where
     /// ingress manager receive buffer.
     pub fn write(&mut self, data: &[u8]) {
         defmt::trace!("Write: \"{=[u8]:a}\"", data);
+        self.last_write = self.clock.now_ms();
 
         if self.buf.extend_from_slice(data).is_err() {
             defmt::error!(
@@ -118,12 +132,12 @@ where
     /// This can be useful for custom flowcontrol implementations
     #[allow(clippy::unused_self)]
     pub fn capacity(&self) -> usize {
-        BufLen::to_usize()
+        BUF_LEN
     }
 
     /// Notify the client that an appropriate response code, or error has been
     /// received
-    fn notify_response(&mut self, resp: Result<Vec<u8, BufLen>, InternalError>) {
+    fn notify_response(&mut self, resp: Result<Vec<u8, BUF_LEN>, InternalError>) {
         match &resp {
             Ok(r) => {
                 if r.is_empty() {
@@ -144,7 +158,7 @@ where
 
     /// Notify the client that an unsolicited response code (URC) has been
     /// received
-    fn notify_urc(&mut self, resp: Vec<u8, BufLen>) {
+    fn notify_urc(&mut self, resp: Vec<u8, BUF_LEN>) {
         defmt::debug!("Received response: \"{=[u8]:a}\"", &resp);
 
         if self.urc_p.ready() {
@@ -172,17 +186,50 @@ where
         }
     }
 
-    pub fn digest(&mut self) {
-        for _ in 0..5 {
+    /// Discard a partially received buffer, if no new bytes have been
+    /// received within `config.inter_char_timeout`.
+    fn handle_inter_char_timeout(&mut self) {
+        if let Some(timeout) = self.config.inter_char_timeout {
+            if !self.buf.is_empty() && self.clock.elapsed_ms(self.last_write) >= timeout {
+                defmt::warn!(
+                    "Inter-character timeout, clearing partial buffer [{=[u8]:a}]",
+                    &self.buf
+                );
+                if self.digester.is_receiving_response() {
+                    self.notify_response(Err(InternalError::InvalidResponse));
+                }
+                self.digester.reset();
+                self.buf.clear();
+            }
+        }
+    }
+
+    /// Digest the received data, handling at most `config.digest_budget`
+    /// responses or URCs.
+    ///
+    /// Returns `true` if the budget was exhausted, meaning that more work
+    /// might remain, in which case `digest` should be called again soon.
+    pub fn digest(&mut self) -> bool {
+        for _ in 0..self.config.digest_budget.max(1) {
             // Handle commands every loop to catch timeouts asap
             self.handle_com();
 
             match self.digester.digest(&mut self.buf, &mut self.urc_matcher) {
-                DigestResult::None => return,
+                DigestResult::None => {
+                    self.handle_inter_char_timeout();
+                    return false;
+                }
                 DigestResult::Urc(urc_line) => self.notify_urc(urc_line),
                 DigestResult::Response(resp) => self.notify_response(resp),
             };
         }
+        true
+    }
+
+    /// Keep digesting the received data, until no more responses or URCs can
+    /// be handled.
+    pub fn digest_until_idle(&mut self) {
+        while self.digest() {}
     }
 }
 
@@ -190,18 +237,19 @@ where
 mod test {
     use super::*;
     use crate::queues::{ComQueue, ResQueue, UrcQueue};
-    use heapless::{consts, spsc::Queue};
+    use core::cell::Cell;
+    use heapless::spsc::Queue;
 
-    type TestRxBufLen = consts::U256;
-    type TestUrcCapacity = consts::U10;
+    const TEST_RX_BUF_LEN: usize = 256;
+    const TEST_URC_QUEUE_LEN: usize = 10;
 
     #[test]
     fn overflow() {
-        static mut RES_Q: ResQueue<TestRxBufLen> = Queue(heapless::i::Queue::u8());
+        static mut RES_Q: ResQueue<TEST_RX_BUF_LEN> = Queue::new();
         let (res_p, mut res_c) = unsafe { RES_Q.split() };
-        static mut URC_Q: UrcQueue<TestRxBufLen, TestUrcCapacity> = Queue(heapless::i::Queue::u8());
+        static mut URC_Q: UrcQueue<TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> = Queue::new();
         let (urc_p, _urc_c) = unsafe { URC_Q.split() };
-        static mut COM_Q: ComQueue = Queue(heapless::i::Queue::u8());
+        static mut COM_Q: ComQueue = Queue::new();
         let (_com_p, com_c) = unsafe { COM_Q.split() };
 
         let mut ingress = IngressManager::with_customs(
@@ -210,6 +258,8 @@ mod test {
             com_c,
             DefaultUrcMatcher::default(),
             DefaultDigester::default(),
+            NoClock,
+            Config::default(),
         );
 
         ingress.write(b"+USORD: 3,266,\"");
@@ -220,4 +270,104 @@ mod test {
         ingress.digest();
         assert_eq!(res_c.dequeue().unwrap(), Err(InternalError::Overflow));
     }
+
+    #[derive(Clone)]
+    struct ClockMock<'a> {
+        time: &'a Cell<u32>,
+    }
+
+    impl Clock for ClockMock<'_> {
+        fn now_ms(&self) -> u32 {
+            self.time.get()
+        }
+    }
+
+    #[test]
+    fn inter_char_timeout() {
+        static mut RES_Q: ResQueue<TEST_RX_BUF_LEN> = Queue::new();
+        let (res_p, mut res_c) = unsafe { RES_Q.split() };
+        static mut URC_Q: UrcQueue<TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> = Queue::new();
+        let (urc_p, _urc_c) = unsafe { URC_Q.split() };
+        static mut COM_Q: ComQueue = Queue::new();
+        let (_com_p, com_c) = unsafe { COM_Q.split() };
+
+        let time = Cell::new(u32::MAX - 50);
+        let mut ingress = IngressManager::with_customs(
+            res_p,
+            urc_p,
+            com_c,
+            DefaultUrcMatcher::default(),
+            DefaultDigester::default(),
+            ClockMock { time: &time },
+            Config::default().inter_char_timeout(100),
+        );
+
+        // Partial garbage, while not awaiting a response, is silently discarded
+        ingress.write(b"ab");
+        ingress.digest();
+        assert_eq!(ingress.len(), 2);
+        time.set(time.get().wrapping_add(100));
+        ingress.digest();
+        assert!(ingress.is_empty());
+        assert!(res_c.dequeue().is_none());
+
+        // Partial response is delivered as an invalid response
+        ingress.write(b"AT+CGMI\r\n+CGMI: u-bl");
+        ingress.digest();
+        time.set(time.get().wrapping_add(99));
+        ingress.digest();
+        assert!(!ingress.is_empty());
+        assert!(res_c.dequeue().is_none());
+
+        time.set(time.get().wrapping_add(1));
+        ingress.digest();
+        assert!(ingress.is_empty());
+        assert_eq!(
+            res_c.dequeue().unwrap(),
+            Err(InternalError::InvalidResponse)
+        );
+
+        // The next response is received normally
+        ingress.write(b"AT\r\r\nOK\r\n");
+        ingress.digest();
+        ingress.digest();
+        assert_eq!(res_c.dequeue().unwrap(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn digest_budget() {
+        static mut RES_Q: ResQueue<TEST_RX_BUF_LEN> = Queue::new();
+        let (res_p, _res_c) = unsafe { RES_Q.split() };
+        static mut URC_Q: UrcQueue<TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> = Queue::new();
+        let (urc_p, mut urc_c) = unsafe { URC_Q.split() };
+        static mut COM_Q: ComQueue = Queue::new();
+        let (_com_p, com_c) = unsafe { COM_Q.split() };
+
+        let mut ingress = IngressManager::with_customs(
+            res_p,
+            urc_p,
+            com_c,
+            DefaultUrcMatcher::default(),
+            DefaultDigester::default(),
+            NoClock,
+            Config::default().digest_budget(2),
+        );
+
+        for _ in 0..5 {
+            ingress.write(b"+UMWI: 0,1\r\n");
+        }
+
+        assert!(ingress.digest());
+        assert!(urc_c.dequeue().is_some());
+        assert!(urc_c.dequeue().is_some());
+        assert!(urc_c.dequeue().is_none());
+
+        ingress.digest_until_idle();
+        for _ in 0..3 {
+            assert!(urc_c.dequeue().is_some());
+        }
+        assert!(urc_c.dequeue().is_none());
+        assert!(ingress.is_empty());
+        assert!(!ingress.digest());
+    }
 }
@@ -17,7 +17,7 @@
 //! ```
 //! use atat::{AtatCmd, AtatResp, Error, InternalError, GenericError};
 //! use core::fmt::Write;
-//! use heapless::{consts, String, Vec};
+//! use heapless::{String, Vec};
 //!
 //! pub struct SetGreetingText<'a> {
 //!     pub text: &'a str,
@@ -30,37 +30,35 @@
 //! impl AtatResp for NoResponse {};
 //!
 //! pub struct GreetingText {
-//!     pub text: String<consts::U64>,
+//!     pub text: String<64>,
 //! };
 //!
 //! impl AtatResp for GreetingText {};
 //!
-//! impl<'a> AtatCmd for SetGreetingText<'a> {
-//!     type CommandLen = consts::U64;
-//!     type Response = NoResponse;
+//! impl<'a> AtatCmd<64> for SetGreetingText<'a> {
+//!     type Response<'resp> = NoResponse;
 //!     type Error = GenericError;
 //!
-//!     fn as_bytes(&self) -> Vec<u8, Self::CommandLen> {
-//!         let mut buf: Vec<u8, Self::CommandLen> = Vec::new();
+//!     fn as_bytes(&self) -> Vec<u8, 64> {
+//!         let mut buf: Vec<u8, 64> = Vec::new();
 //!         write!(buf, "AT+CSGT={}", self.text);
 //!         buf
 //!     }
 //!
-//!     fn parse(&self, resp: Result<&[u8], &InternalError>) -> Result<Self::Response, Error<Self::Error>> {
+//!     fn parse<'resp>(&self, resp: Result<&'resp [u8], &InternalError>) -> Result<Self::Response<'resp>, Error<Self::Error>> {
 //!         Ok(NoResponse)
 //!     }
 //! }
 //!
-//! impl AtatCmd for GetGreetingText {
-//!     type CommandLen = consts::U8;
-//!     type Response = GreetingText;
+//! impl AtatCmd<8> for GetGreetingText {
+//!     type Response<'resp> = GreetingText;
 //!     type Error = GenericError;
 //!
-//!     fn as_bytes(&self) -> Vec<u8, Self::CommandLen> {
+//!     fn as_bytes(&self) -> Vec<u8, 8> {
 //!         Vec::from_slice(b"AT+CSGT?").unwrap()
 //!     }
 //!
-//!     fn parse(&self, resp: Result<&[u8], &InternalError>) -> Result<Self::Response, Error<Self::Error>> {
+//!     fn parse<'resp>(&self, resp: Result<&'resp [u8], &InternalError>) -> Result<Self::Response<'resp>, Error<Self::Error>> {
 //!         // Parse resp into `GreetingText`
 //!         Ok(GreetingText {
 //!             text: String::from(core::str::from_utf8(resp.unwrap()).unwrap()),
@@ -72,7 +70,7 @@
 //! ### Same example with `atat_derive`:
 //! ```
 //! use atat::atat_derive::{AtatCmd, AtatResp};
-//! use heapless::{consts, String};
+//! use heapless::String;
 //!
 //! #[derive(Clone, AtatCmd)]
 //! #[at_cmd("+CSGT", NoResponse)]
@@ -91,7 +89,7 @@
 //! #[derive(Clone, AtatResp)]
 //! pub struct GreetingText {
 //!     #[at_arg(position = 0)]
-//!     pub text: String<consts::U64>,
+//!     pub text: String<64>,
 //! };
 //! ```
 //!
@@ -112,7 +110,7 @@
 //!
 //! use atat::{atat_derive::{AtatResp, AtatCmd}};
 //!
-//! use heapless::{consts, spsc::Queue, String};
+//! use heapless::{spsc::Queue, String};
 //!
 //! use crate::rt::entry;
 //! static mut INGRESS: Option<atat::IngressManager> = None;
@@ -142,7 +140,6 @@
 //!     let rx = gpioa.pa3.into_af7(&mut gpioa.moder, &mut gpioa.afrl);
 //!
 //!     let mut timer = Timer::tim7(p.TIM7, 1.hz(), clocks, &mut rcc.apb1r1);
-//!     let at_timer = Timer::tim6(p.TIM6, 100.hz(), clocks, &mut rcc.apb1r1);
 //!
 //!     let mut serial = Serial::usart2(
 //!         p.USART2,
@@ -154,9 +151,9 @@
 //!
 //!     serial.listen(Rxne);
 //!
-//!     static mut RES_QUEUE: ResQueue<consts::U256> = Queue(heapless::i::Queue::u8());
-//!     static mut URC_QUEUE: UrcQueue<consts::U256, consts::U10> = Queue(heapless::i::Queue::u8());
-//!     static mut COM_QUEUE: ComQueue = Queue(heapless::i::Queue::u8());
+//!     static mut RES_QUEUE: ResQueue<256> = Queue::new();
+//!     static mut URC_QUEUE: UrcQueue<256, 10> = Queue::new();
+//!     static mut COM_QUEUE: ComQueue = Queue::new();
 //!
 //!     let queues = Queues {
 //!         res_queue: unsafe { RES_QUEUE.split() },
@@ -165,8 +162,9 @@
 //!     };
 //!
 //!     let (tx, rx) = serial.split();
+//!     // `MillisClock` is any type implementing `atat::Clock`
 //!     let (mut client, ingress) =
-//!         ClientBuilder::new(tx, timer, atat::Config::new(atat::Mode::Timeout)).build(queues);
+//!         ClientBuilder::new(tx, MillisClock, atat::Config::new(atat::Mode::Timeout)).build(queues);
 //!
 //!     unsafe { INGRESS = Some(ingress) };
 //!     unsafe { RX = Some(rx) };
@@ -234,13 +232,16 @@
 
 mod builder;
 mod client;
+mod clock;
 mod digest;
 mod error;
 pub mod helpers;
 mod ingress_manager;
 mod queues;
+mod shared;
 mod traits;
 mod urc_matcher;
+mod wake_up;
 
 #[cfg(feature = "derive")]
 pub use atat_derive;
@@ -248,25 +249,25 @@ pub use atat_derive;
 pub mod derive;
 
 #[cfg(feature = "derive")]
-pub use self::derive::AtatLen;
+pub use self::derive::{assert_buf_len, response_len, AtatLen, AtatPrefixLen};
 
 #[cfg(feature = "derive")]
 pub use serde_at;
 
-#[cfg(feature = "derive")]
-pub use typenum;
-
 #[cfg(feature = "derive")]
 pub use heapless;
 
 pub use builder::ClientBuilder;
 pub use client::{Client, Mode};
+pub use clock::{Clock, NoClock};
 pub use digest::{DefaultDigester, DigestResult, Digester};
-pub use error::{Error, GenericError, InternalError};
+pub use error::{Error, GenericError, InternalError, ParseError};
 pub use ingress_manager::IngressManager;
 pub use queues::{ComQueue, Queues, ResQueue, UrcQueue};
+pub use shared::{Mutex, SharedClient};
 pub use traits::{AtatClient, AtatCmd, AtatResp, AtatUrc};
 pub use urc_matcher::{DefaultUrcMatcher, UrcMatcher, UrcMatcherResult};
+pub use wake_up::{NoPin, WakeUp};
 
 /// Commands that can be sent from the client to the ingress manager, for
 /// configuration after initial setup. This is also used for stuff like clearing
@@ -289,6 +290,10 @@ pub enum Command {
 pub struct Config {
     mode: Mode,
     cmd_cooldown: u32,
+    wake_up: WakeUp,
+    wake_up_idle: u32,
+    inter_char_timeout: Option<u32>,
+    digest_budget: usize,
 }
 
 impl Default for Config {
@@ -296,6 +301,10 @@ impl Default for Config {
         Self {
             mode: Mode::Blocking,
             cmd_cooldown: 20,
+            wake_up: WakeUp::Disabled,
+            wake_up_idle: 0,
+            inter_char_timeout: None,
+            digest_budget: 5,
         }
     }
 }
@@ -314,6 +323,36 @@ impl Config {
         self.cmd_cooldown = ms;
         self
     }
+
+    /// Apply the `wake_up` policy before sending a command, if the link has
+    /// been idle for more than `idle_ms`.
+    #[must_use]
+    pub const fn wake_up(mut self, wake_up: WakeUp, idle_ms: u32) -> Self {
+        self.wake_up = wake_up;
+        self.wake_up_idle = idle_ms;
+        self
+    }
+
+    /// Discard any partially received data in the ingress manager, if no new
+    /// bytes have been received for `ms` milliseconds. If a response was
+    /// pending, the client will receive an [`Error::InvalidResponse`].
+    ///
+    /// [`Error::InvalidResponse`]: enum.Error.html#variant.InvalidResponse
+    #[must_use]
+    pub const fn inter_char_timeout(mut self, ms: u32) -> Self {
+        self.inter_char_timeout = Some(ms);
+        self
+    }
+
+    /// Maximum number of responses and URCs handled by a single call to
+    /// [`IngressManager::digest`]. A budget of zero is treated as one.
+    ///
+    /// [`IngressManager::digest`]: struct.IngressManager.html#method.digest
+    #[must_use]
+    pub const fn digest_budget(mut self, budget: usize) -> Self {
+        self.digest_budget = budget;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -1,47 +1,75 @@
 //! Type definitions for the queues used in this crate.
+//!
+//! Note that a `heapless::spsc::Queue<T, N>` holds at most `N - 1` items.
 
+use heapless::spsc::{Consumer, Producer, Queue};
 use heapless::Vec;
-use heapless::{
-    consts,
-    spsc::{Consumer, Producer, Queue},
-    ArrayLength,
-};
 
 pub use crate::error::InternalError;
 pub use crate::Command;
 
 // Queue item types
 pub type ComItem = Command;
-pub type ResItem<BufLen> = Result<Vec<u8, BufLen>, InternalError>;
-pub type UrcItem<BufLen> = Vec<u8, BufLen>;
+pub type ResItem<const BUF_LEN: usize> = Result<Vec<u8, BUF_LEN>, InternalError>;
+pub type UrcItem<const BUF_LEN: usize> = Vec<u8, BUF_LEN>;
 
-pub type ResCapacity = consts::U1;
-pub type ComCapacity = consts::U3;
+pub const RES_CAPACITY: usize = 1;
+pub const COM_CAPACITY: usize = 3;
 
 // Consumers
-pub type ComConsumer = Consumer<'static, ComItem, ComCapacity, u8>;
-pub type ResConsumer<BufLen> = Consumer<'static, ResItem<BufLen>, ResCapacity, u8>;
-pub type UrcConsumer<BufLen, UrcCapacity> = Consumer<'static, UrcItem<BufLen>, UrcCapacity, u8>;
+pub type ComConsumer = Consumer<'static, ComItem, { COM_CAPACITY + 1 }>;
+pub type ResConsumer<const BUF_LEN: usize> =
+    Consumer<'static, ResItem<BUF_LEN>, { RES_CAPACITY + 1 }>;
+pub type UrcConsumer<const BUF_LEN: usize, const URC_QUEUE_LEN: usize> =
+    Consumer<'static, UrcItem<BUF_LEN>, URC_QUEUE_LEN>;
 
 // Producers
-pub type ComProducer = Producer<'static, ComItem, ComCapacity, u8>;
-pub type ResProducer<BufLen> = Producer<'static, ResItem<BufLen>, ResCapacity, u8>;
-pub type UrcProducer<BufLen, UrcCapacity> = Producer<'static, UrcItem<BufLen>, UrcCapacity, u8>;
+pub type ComProducer = Producer<'static, ComItem, { COM_CAPACITY + 1 }>;
+pub type ResProducer<const BUF_LEN: usize> =
+    Producer<'static, ResItem<BUF_LEN>, { RES_CAPACITY + 1 }>;
+pub type UrcProducer<const BUF_LEN: usize, const URC_QUEUE_LEN: usize> =
+    Producer<'static, UrcItem<BUF_LEN>, URC_QUEUE_LEN>;
 
 // Queues
-pub type ComQueue = Queue<ComItem, ComCapacity, u8>;
-pub type ResQueue<BufLen> = Queue<ResItem<BufLen>, ResCapacity, u8>;
-pub type UrcQueue<BufLen, UrcCapacity> = Queue<UrcItem<BufLen>, UrcCapacity, u8>;
-
-pub struct Queues<BufLen, UrcCapacity>
-where
-    BufLen: ArrayLength<u8>,
-    UrcCapacity: ArrayLength<UrcItem<BufLen>>,
-{
-    pub res_queue: (ResProducer<BufLen>, ResConsumer<BufLen>),
+pub type ComQueue = Queue<ComItem, { COM_CAPACITY + 1 }>;
+pub type ResQueue<const BUF_LEN: usize> = Queue<ResItem<BUF_LEN>, { RES_CAPACITY + 1 }>;
+/// Queue of URCs, holding at most `URC_QUEUE_LEN - 1` of them.
+///
+/// `URC_QUEUE_LEN` must be at least 2, which is checked at compile time when
+/// building the client and ingress manager:
+///
+/// ```compile_fail
+/// use atat::{ComQueue, IngressManager, ResQueue, UrcQueue};
+/// use heapless::spsc::Queue;
+///
+/// static mut RES_QUEUE: ResQueue<256> = Queue::new();
+/// static mut URC_QUEUE: UrcQueue<256, 1> = Queue::new();
+/// static mut COM_QUEUE: ComQueue = Queue::new();
+///
+/// let (res_p, _) = unsafe { RES_QUEUE.split() };
+/// let (urc_p, _) = unsafe { URC_QUEUE.split() };
+/// let (_, com_c) = unsafe { COM_QUEUE.split() };
+/// let ingress = IngressManager::new(res_p, urc_p, com_c);
+/// ```
+pub type UrcQueue<const BUF_LEN: usize, const URC_QUEUE_LEN: usize> =
+    Queue<UrcItem<BUF_LEN>, URC_QUEUE_LEN>;
+
+/// Compile time check of `URC_QUEUE_LEN`, as a URC queue of length 1 can not
+/// hold any URC
+pub(crate) struct UrcQueueLen<const URC_QUEUE_LEN: usize>;
+
+impl<const URC_QUEUE_LEN: usize> UrcQueueLen<URC_QUEUE_LEN> {
+    pub(crate) const ASSERT: () = assert!(
+        URC_QUEUE_LEN >= 2,
+        "`URC_QUEUE_LEN` must be at least 2, as the URC queue holds `URC_QUEUE_LEN - 1` URCs"
+    );
+}
+
+pub struct Queues<const BUF_LEN: usize, const URC_QUEUE_LEN: usize> {
+    pub res_queue: (ResProducer<BUF_LEN>, ResConsumer<BUF_LEN>),
     pub urc_queue: (
-        UrcProducer<BufLen, UrcCapacity>,
-        UrcConsumer<BufLen, UrcCapacity>,
+        UrcProducer<BUF_LEN, URC_QUEUE_LEN>,
+        UrcConsumer<BUF_LEN, URC_QUEUE_LEN>,
     ),
     pub com_queue: (ComProducer, ComConsumer),
 }
@@ -0,0 +1,280 @@
+use crate::error::Error;
+use crate::traits::{AtatClient, AtatCmd, AtatUrc};
+use crate::Mode;
+
+/// Mutual exclusion primitive guarding a shared resource.
+///
+/// This mirrors the `Mutex` trait of [`rtic_core`], and is implemented by
+/// wrapping the resource proxy of the RTIC application, or a critical section
+/// based mutex:
+///
+/// ```ignore
+/// struct ClientProxy<'a>(resources::client<'a>);
+///
+/// impl<'a> atat::Mutex for ClientProxy<'a> {
+///     type T = MyClient;
+///
+///     fn lock<R>(&mut self, f: impl FnOnce(&mut Self::T) -> R) -> R {
+///         rtic::Mutex::lock(&mut self.0, f)
+///     }
+/// }
+/// ```
+///
+/// [`rtic_core`]: https://crates.io/crates/rtic-core
+pub trait Mutex {
+    /// Data protected by the mutex
+    type T;
+
+    /// Creates a critical section and grants temporary access to the protected
+    /// data
+    fn lock<R>(&mut self, f: impl FnOnce(&mut Self::T) -> R) -> R;
+}
+
+impl<M> Mutex for &mut M
+where
+    M: Mutex,
+{
+    type T = M::T;
+
+    fn lock<R>(&mut self, f: impl FnOnce(&mut Self::T) -> R) -> R {
+        M::lock(self, f)
+    }
+}
+
+/// [`AtatClient`] wrapper, allowing a single client to be shared between
+/// multiple tasks.
+///
+/// Every command transaction, from sending the command until the response is
+/// received, is executed while holding the lock. This ensures that commands
+/// from different tasks cannot interleave, and that a task will never receive
+/// the response to a command sent by another task. As a consequence, `send`
+/// always blocks until the transaction has completed, so the ingress manager
+/// must be digested from a context that can preempt the lock.
+///
+/// The client must be configured with [`Mode::Timeout`], so a response that
+/// never arrives times out after `cmd.max_timeout_ms()`, rather than blocking
+/// every task sharing the client forever. [`new`] fails otherwise.
+///
+/// [`AtatClient`]: trait.AtatClient.html
+/// [`Mode::Timeout`]: enum.Mode.html#variant.Timeout
+/// [`new`]: #method.new
+pub struct SharedClient<M> {
+    mutex: M,
+    mode: Mode,
+}
+
+impl<M> SharedClient<M>
+where
+    M: Mutex,
+    M::T: AtatClient,
+{
+    /// Share the client guarded by `mutex`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `mutex` back if the client isn't configured with
+    /// `Mode::Timeout`
+    pub fn new(mut mutex: M) -> Result<Self, M> {
+        let mode = mutex.lock(|client| client.get_mode());
+        if mode != Mode::Timeout {
+            return Err(mutex);
+        }
+        Ok(Self { mutex, mode })
+    }
+
+    /// Release the underlying mutex
+    pub fn into_inner(self) -> M {
+        self.mutex
+    }
+}
+
+impl<M> AtatClient for SharedClient<M>
+where
+    M: Mutex,
+    M::T: AtatClient,
+{
+    fn send_with<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R,
+    {
+        self.mutex.lock(|client| {
+            // `f` is only called once the response is received, which may be
+            // on either of the calls below
+            let mut f = Some(f);
+            let result = match client.send_with(cmd, |resp| f.take().map(|f| f(resp))) {
+                Err(nb::Error::WouldBlock) => {
+                    nb::block!(client.check_response_with(cmd, |resp| f.take().map(|f| f(resp))))
+                        .map_err(nb::Error::Other)
+                }
+                result => result,
+            };
+            result.and_then(|r| r.ok_or(nb::Error::WouldBlock))
+        })
+    }
+
+    fn peek_urc_with<URC: AtatUrc, F: FnOnce(URC::Response) -> bool>(&mut self, f: F) {
+        self.mutex.lock(|client| client.peek_urc_with::<URC, F>(f))
+    }
+
+    fn check_response_with<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R,
+    {
+        self.mutex.lock(|client| client.check_response_with(cmd, f))
+    }
+
+    fn get_mode(&self) -> Mode {
+        self.mode
+    }
+
+    fn reset(&mut self) {
+        self.mutex.lock(|client| client.reset())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::queues::{ComQueue, ResQueue, UrcQueue};
+    use crate::{self as atat, Client, Clock, Config};
+    use atat_derive::{AtatCmd, AtatResp};
+    use core::cell::RefCell;
+    use embedded_hal::serial;
+    use heapless::{spsc::Queue, String, Vec};
+
+    const TEST_RX_BUF_LEN: usize = 256;
+    const TEST_URC_QUEUE_LEN: usize = 10;
+
+    struct TxMock {
+        s: String<64>,
+    }
+
+    impl serial::Write<u8> for TxMock {
+        type Error = ();
+
+        fn try_write(&mut self, c: u8) -> nb::Result<(), Self::Error> {
+            self.s.push(c as char).map_err(nb::Error::Other)
+        }
+
+        fn try_flush(&mut self) -> nb::Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    /// Clock advancing by one millisecond every time it is read
+    struct ClockMock {
+        time: core::cell::Cell<u32>,
+    }
+
+    impl Clock for ClockMock {
+        fn now_ms(&self) -> u32 {
+            let now = self.time.get();
+            self.time.set(now.wrapping_add(1));
+            now
+        }
+    }
+
+    struct RefCellMutex<'a, T>(&'a RefCell<T>);
+
+    impl<T> Mutex for RefCellMutex<'_, T> {
+        type T = T;
+
+        fn lock<R>(&mut self, f: impl FnOnce(&mut Self::T) -> R) -> R {
+            f(&mut self.0.borrow_mut())
+        }
+    }
+
+    #[derive(Clone, AtatResp, PartialEq, Debug)]
+    pub struct TestResponse {
+        #[at_arg(position = 0)]
+        pub value: u8,
+    }
+
+    #[derive(Clone, AtatCmd)]
+    #[at_cmd("+TEST", TestResponse)]
+    pub struct TestCmd {
+        #[at_arg(position = 0)]
+        pub value: u8,
+    }
+
+    macro_rules! setup {
+        ($mode:expr) => {{
+            static mut RES_Q: ResQueue<TEST_RX_BUF_LEN> = Queue::new();
+            let (res_p, res_c) = unsafe { RES_Q.split() };
+            static mut URC_Q: UrcQueue<TEST_RX_BUF_LEN, TEST_URC_QUEUE_LEN> = Queue::new();
+            let (_urc_p, urc_c) = unsafe { URC_Q.split() };
+            static mut COM_Q: ComQueue = Queue::new();
+            let (com_p, _com_c) = unsafe { COM_Q.split() };
+
+            let client = RefCell::new(Client::new(
+                TxMock { s: String::new() },
+                res_c,
+                urc_c,
+                com_p,
+                ClockMock {
+                    time: core::cell::Cell::new(0),
+                },
+                Config::new($mode).cmd_cooldown(0),
+            ));
+            (client, res_p)
+        }};
+    }
+
+    #[test]
+    fn shared_send() {
+        let (client, mut res_p) = setup!(Mode::Timeout);
+
+        let mut task_a = SharedClient::new(RefCellMutex(&client)).ok().unwrap();
+        let mut task_b = SharedClient::new(RefCellMutex(&client)).ok().unwrap();
+        assert_eq!(task_a.get_mode(), Mode::Timeout);
+
+        // The whole transaction completes within the lock
+        res_p
+            .enqueue(Ok(Vec::from_slice(b"+TEST: 1").unwrap()))
+            .unwrap();
+        assert_eq!(
+            task_a.send(&TestCmd { value: 1 }),
+            Ok(TestResponse { value: 1 })
+        );
+
+        res_p
+            .enqueue(Ok(Vec::from_slice(b"+TEST: 2").unwrap()))
+            .unwrap();
+        assert_eq!(
+            task_b.send(&TestCmd { value: 2 }),
+            Ok(TestResponse { value: 2 })
+        );
+    }
+
+    #[test]
+    fn shared_send_timeout() {
+        let (client, _res_p) = setup!(Mode::Timeout);
+
+        // The lock is released once the lost response times out
+        let mut task_a = SharedClient::new(RefCellMutex(&client)).ok().unwrap();
+        assert_eq!(
+            task_a.send(&TestCmd { value: 1 }),
+            Err(nb::Error::Other(Error::Timeout))
+        );
+        assert!(client.try_borrow_mut().is_ok());
+    }
+
+    #[test]
+    fn shared_requires_timeout_mode() {
+        let (client, _res_p) = setup!(Mode::Blocking);
+        assert!(SharedClient::new(RefCellMutex(&client)).is_err());
+
+        let (client, _res_p) = setup!(Mode::NonBlocking);
+        assert!(SharedClient::new(RefCellMutex(&client)).is_err());
+    }
+}
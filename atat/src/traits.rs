@@ -1,10 +1,10 @@
 use crate::Mode;
 use crate::{
-    error::{Error, InternalError},
+    error::{Error, InternalError, ParseError},
     GenericError,
 };
 use core::str::FromStr;
-use heapless::{ArrayLength, Vec};
+use heapless::Vec;
 
 /// This trait needs to be implemented for every response type.
 ///
@@ -13,7 +13,7 @@ use heapless::{ArrayLength, Vec};
 /// use atat::AtatResp;
 ///
 /// pub struct GreetingText {
-///     pub text: heapless::String<heapless::consts::U64>,
+///     pub text: heapless::String<64>,
 /// }
 ///
 /// impl AtatResp for GreetingText {}
@@ -48,46 +48,90 @@ pub trait AtatUrc {
 ///
 /// impl AtatResp for NoResponse {};
 ///
-/// impl<'a> AtatCmd for SetGreetingText<'a> {
-///     type CommandLen = heapless::consts::U64;
-///     type Response = NoResponse;
+/// impl<'a> AtatCmd<64> for SetGreetingText<'a> {
+///     type Response<'resp> = NoResponse;
 ///     type Error = GenericError;
 ///
-///     fn as_bytes(&self) -> Vec<u8, Self::CommandLen> {
-///         let mut buf: Vec<u8, Self::CommandLen> = Vec::new();
+///     fn as_bytes(&self) -> Vec<u8, 64> {
+///         let mut buf: Vec<u8, 64> = Vec::new();
 ///         write!(buf, "AT+CSGT={}", self.text);
 ///         buf
 ///     }
 ///
-///     fn parse(&self, resp: Result<&[u8], &InternalError>) -> Result<Self::Response, Error<Self::Error>> {
+///     fn parse<'resp>(&self, resp: Result<&'resp [u8], &InternalError>) -> Result<Self::Response<'resp>, Error<Self::Error>> {
 ///         Ok(NoResponse)
 ///     }
 /// }
 /// ```
-pub trait AtatCmd {
-    /// The max length of the command.
+///
+/// `LEN` is the max length of the command. Example: For the command "AT+RST"
+/// you would implement `AtatCmd<6>`.
+pub trait AtatCmd<const LEN: usize> {
+    /// The type of the response. Must implement the `AtatResp` trait.
     ///
-    /// Example: For the command "AT+RST" you would specify
+    /// The response may borrow from the response buffer for `'a`, eg. to
+    /// consume large socket payloads without copying them. Such responses
+    /// are received with [`AtatClient::send_with`].
     ///
-    /// ```
-    /// type CommandLen = heapless::consts::U6;
-    /// ```
-    type CommandLen: ArrayLength<u8>;
-
-    /// The type of the response. Must implement the `AtatResp` trait.
-    type Response: AtatResp;
+    /// [`AtatClient::send_with`]: trait.AtatClient.html#tymethod.send_with
+    type Response<'a>: AtatResp;
 
     /// The type of the error.
     type Error: FromStr + defmt::Format;
 
     /// Return the command as a heapless `Vec` of bytes.
-    fn as_bytes(&self) -> Vec<u8, Self::CommandLen>;
+    fn as_bytes(&self) -> Vec<u8, LEN>;
+
+    /// Return the command as a heapless `Vec` of bytes, or an error if it
+    /// cannot be serialized.
+    ///
+    /// This is used by the client when sending the command. The default
+    /// implementation never fails, and defers to
+    /// [`as_bytes`](#tymethod.as_bytes).
+    fn try_as_bytes(&self) -> Result<Vec<u8, LEN>, Error<Self::Error>> {
+        Ok(self.as_bytes())
+    }
+
+    /// Write the command into `writer`, returning the number of bytes
+    /// written.
+    ///
+    /// This is used by the client to stream the command straight to the
+    /// serial port, without materialising it on the stack. The default
+    /// implementation writes the bytes of
+    /// [`try_as_bytes`](#method.try_as_bytes), whereas derived commands are
+    /// serialized straight into `writer`.
+    fn write_into<W>(&self, writer: &mut W) -> Result<usize, Error<Self::Error>>
+    where
+        W: serde_at::ser::Write + ?Sized,
+    {
+        let bytes = self.try_as_bytes()?;
+        writer.write_bytes(&bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Write the command into `buf`, returning the number of bytes written,
+    /// or `Error::Overflow` if it doesn't fit.
+    fn serialize_into(&self, buf: &mut [u8]) -> Result<usize, Error<Self::Error>> {
+        let mut writer = buf;
+        self.write_into(&mut writer)
+    }
+
+    /// Check the arguments of the command, before it is sent.
+    ///
+    /// The client refuses to send commands failing validation, returning the
+    /// error instead. Derived commands check the `range`, `max_len` and
+    /// `charset` constraints of their fields, returning
+    /// `Error::InvalidArgument`. The default implementation accepts any
+    /// arguments.
+    fn validate(&self) -> Result<(), Error<Self::Error>> {
+        Ok(())
+    }
 
     /// Parse the response into a `Self::Response` or `Error<Self::Error>` instance.
-    fn parse(
+    fn parse<'a>(
         &self,
-        resp: Result<&[u8], &InternalError>,
-    ) -> Result<Self::Response, Error<Self::Error>>;
+        resp: Result<&'a [u8], &InternalError>,
+    ) -> Result<Self::Response<'a>, Error<Self::Error>>;
 
     /// Whether or not this command can be aborted.
     fn can_abort(&self) -> bool {
@@ -126,7 +170,32 @@ pub trait AtatClient {
     /// This function will also make sure that atleast `self.config.cmd_cooldown`
     /// has passed since the last response or URC has been received, to allow
     /// the slave AT device time to deliver URC's.
-    fn send<A: AtatCmd>(&mut self, cmd: &A) -> nb::Result<A::Response, Error<A::Error>>;
+    ///
+    /// Responses borrowing from the response buffer must be received with
+    /// [`send_with`](#tymethod.send_with) instead.
+    fn send<A, R, const LEN: usize>(&mut self, cmd: &A) -> nb::Result<R, Error<A::Error>>
+    where
+        A: for<'a> AtatCmd<LEN, Response<'a> = R>,
+    {
+        self.send_with(cmd, |resp| resp)
+    }
+
+    /// Send an AT command, passing the response to `f`, and returning its
+    /// result.
+    ///
+    /// The response may borrow from the response buffer, eg. to consume a
+    /// large socket payload without copying it. The client holds on to the
+    /// response until `f` returns.
+    ///
+    /// Otherwise this behaves like [`send`](#method.send).
+    fn send_with<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R;
 
     /// Checks if there are any URC's (Unsolicited Response Code) in
     /// queue from the ingress manager.
@@ -174,7 +243,26 @@ pub trait AtatClient {
     /// This function is usually only called through [`send`].
     ///
     /// [`send`]: #method.send
-    fn check_response<A: AtatCmd>(&mut self, cmd: &A) -> nb::Result<A::Response, Error<A::Error>>;
+    fn check_response<A, R, const LEN: usize>(&mut self, cmd: &A) -> nb::Result<R, Error<A::Error>>
+    where
+        A: for<'a> AtatCmd<LEN, Response<'a> = R>,
+    {
+        self.check_response_with(cmd, |resp| resp)
+    }
+
+    /// Check if there are any responses enqueued from the ingress manager,
+    /// passing the response to `f`, and returning its result.
+    ///
+    /// This is the counterpart of [`check_response`](#method.check_response)
+    /// for [`send_with`](#tymethod.send_with).
+    fn check_response_with<A, F, R, const LEN: usize>(
+        &mut self,
+        cmd: &A,
+        f: F,
+    ) -> nb::Result<R, Error<A::Error>>
+    where
+        A: AtatCmd<LEN>,
+        F: FnOnce(A::Response<'_>) -> R;
 
     /// Get the configured mode of the client.
     ///
@@ -188,37 +276,35 @@ pub trait AtatClient {
     fn reset(&mut self);
 }
 
-impl<T, L> AtatResp for heapless::Vec<T, L>
-where
-    T: AtatResp,
-    L: ArrayLength<T>,
-{
-}
+impl<T, const L: usize> AtatResp for heapless::Vec<T, L> where T: AtatResp {}
 
-impl<L> AtatResp for heapless::String<L> where L: ArrayLength<u8> {}
+impl<const L: usize> AtatResp for heapless::String<L> {}
 
-impl<L> AtatCmd for heapless::String<L>
-where
-    L: ArrayLength<u8>,
-{
-    type CommandLen = L;
-
-    type Response = heapless::String<heapless::consts::U256>;
+impl<const L: usize> AtatCmd<L> for heapless::String<L> {
+    type Response<'a> = heapless::String<256>;
     type Error = GenericError;
 
-    fn as_bytes(&self) -> Vec<u8, Self::CommandLen> {
+    fn as_bytes(&self) -> Vec<u8, L> {
         self.clone().into_bytes()
     }
 
-    fn parse(
+    fn write_into<W>(&self, writer: &mut W) -> Result<usize, Error<Self::Error>>
+    where
+        W: serde_at::ser::Write + ?Sized,
+    {
+        writer.write_bytes(self.as_str().as_bytes())?;
+        Ok(self.len())
+    }
+
+    fn parse<'a>(
         &self,
-        resp: Result<&[u8], &InternalError>,
-    ) -> Result<Self::Response, Error<Self::Error>> {
-        resp.map(|r| {
-            heapless::String::from_utf8(Vec::from_slice(r).map_err(|_| Error::Parse)?)
-                .map_err(|_| Error::Parse)
-        })
-        .map_err(Error::from)?
+        resp: Result<&'a [u8], &InternalError>,
+    ) -> Result<Self::Response<'a>, Error<Self::Error>> {
+        let utf8 = core::str::from_utf8(resp?).map_err(|_| Error::Parse(ParseError::default()))?;
+        let mut s = heapless::String::new();
+        s.push_str(utf8)
+            .map_err(|_| Error::Parse(ParseError::default()))?;
+        Ok(s)
     }
 }
 
@@ -227,7 +313,7 @@ mod test {
     use super::*;
     use crate as atat;
     use atat_derive::{AtatEnum, AtatResp};
-    use heapless::{consts, String};
+    use heapless::String;
 
     #[derive(Debug, Clone, PartialEq, AtatEnum)]
     pub enum PDPContextStatus {
@@ -246,15 +332,16 @@ mod test {
     }
 
     #[derive(Debug, Clone, AtatResp, PartialEq)]
+    #[at_resp(ignore_trailing)]
     pub struct PDPContextDefinition {
         #[at_arg(position = 0)]
         pub cid: u8,
         #[at_arg(position = 1)]
-        pub pdp_type: String<consts::U6>,
+        pub pdp_type: String<6>,
         #[at_arg(position = 2)]
-        pub apn: String<consts::U99>,
+        pub apn: String<99>,
         #[at_arg(position = 3)]
-        pub pdp_addr: String<consts::U99>,
+        pub pdp_addr: String<99>,
         #[at_arg(position = 4)]
         pub d_comp: u8,
         #[at_arg(position = 5)]
@@ -273,13 +360,13 @@ mod test {
 
     #[test]
     fn single_multi_response() {
-        let mut v = Vec::<_, heapless::consts::U1>::from_slice(&[PDPContextState {
+        let mut v = Vec::<_, 1>::from_slice(&[PDPContextState {
             cid: 1,
             status: PDPContextStatus::Deactivated,
         }])
         .unwrap();
 
-        let mut resp: heapless::Vec<PDPContextState, heapless::consts::U1> =
+        let mut resp: heapless::Vec<PDPContextState, 1> =
             serde_at::from_slice(b"+CGACT: 1,0\r\n").unwrap();
 
         assert_eq!(resp.pop(), v.pop());
@@ -288,7 +375,7 @@ mod test {
 
     #[test]
     fn multi_response() {
-        let mut v = Vec::<_, heapless::consts::U3>::from_slice(&[
+        let mut v = Vec::<_, 3>::from_slice(&[
             PDPContextState {
                 cid: 1,
                 status: PDPContextStatus::Deactivated,
@@ -304,7 +391,7 @@ mod test {
         ])
         .unwrap();
 
-        let mut resp: heapless::Vec<PDPContextState, heapless::consts::U3> =
+        let mut resp: heapless::Vec<PDPContextState, 3> =
             serde_at::from_slice(b"+CGACT: 1,0\r\n+CGACT: 2,1\r\n+CGACT: 3,0").unwrap();
 
         assert_eq!(resp.pop(), v.pop());
@@ -315,7 +402,7 @@ mod test {
 
     #[test]
     fn multi_response_advanced() {
-        let mut v = Vec::<_, heapless::consts::U3>::from_slice(&[
+        let mut v = Vec::<_, 3>::from_slice(&[
             PDPContextDefinition {
                 cid: 2,
                 pdp_type: String::from("IP"),
@@ -355,8 +442,21 @@ mod test {
         ])
         .unwrap();
 
-        let mut resp: heapless::Vec<PDPContextDefinition, heapless::consts::U3> =
-            serde_at::from_slice(b"+CGDCONT: 2,\"IP\",\"em\",\"100.92.188.66\",0,0,0,0,0,0\r\n+CGDCONT: 1,\"IP\",\"STATREAL\",\"0.0.0.0\",0,0\r\n+CGDCONT: 3,\"IP\",\"tim.ibox.it\",\"0.0.0.0\",0,0").unwrap();
+        // The first response includes the trailing `nslpi` parameter
+        let input = b"+CGDCONT: 2,\"IP\",\"em\",\"100.92.188.66\",0,0,0,0,0,0,0\r\n+CGDCONT: 1,\"IP\",\"STATREAL\",\"0.0.0.0\",0,0\r\n+CGDCONT: 3,\"IP\",\"tim.ibox.it\",\"0.0.0.0\",0,0";
+
+        let options = serde_at::DeserializeOptions {
+            trailing_params: serde_at::TrailingParams::Strict,
+        };
+        let strict = serde_at::from_slice_with_options::<heapless::Vec<PDPContextDefinition, 3>>(
+            input, options,
+        );
+        assert_eq!(
+            strict.map_err(|e| e.error),
+            Err(serde_at::de::Error::TrailingCharacters)
+        );
+
+        let mut resp: heapless::Vec<PDPContextDefinition, 3> = serde_at::from_slice(input).unwrap();
 
         assert_eq!(resp.pop(), v.pop());
         assert_eq!(resp.pop(), v.pop());
@@ -0,0 +1,54 @@
+//! Wake-up handling for modules entering power saving modes.
+//!
+//! Many modules (eg. u-blox cellular modules) use the first character received
+//! on the serial line to wake up from power saving, and will discard that
+//! character when no hardware flow control is used. The client can be
+//! configured with a [`WakeUp`] policy through [`Config::wake_up`], which is
+//! applied before sending a command, whenever the link has been idle for
+//! longer than the configured threshold.
+//!
+//! [`WakeUp`]: enum.WakeUp.html
+//! [`Config::wake_up`]: ../struct.Config.html#method.wake_up
+
+use embedded_hal::digital::OutputPin;
+
+/// Policy used to wake up the module before sending a command on an idle link
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum WakeUp {
+    /// Never try to wake up the module
+    Disabled,
+    /// Write a single dummy byte, and wait `delay_ms` before sending the
+    /// command
+    Byte { byte: u8, delay_ms: u32 },
+    /// Write `AT\r`, wait up to `timeout_ms` for its response and discard
+    /// it, then wait `delay_ms` before sending the command. If no response is
+    /// received within `timeout_ms`, the command is sent anyway.
+    Command { timeout_ms: u32, delay_ms: u32 },
+    /// Pulse the wake-up pin given to [`ClientBuilder::with_wake_up_pin`] low
+    /// for `pulse_ms`, and wait `delay_ms` before sending the command.
+    ///
+    /// [`ClientBuilder::with_wake_up_pin`]: ../struct.ClientBuilder.html#method.with_wake_up_pin
+    Pin { pulse_ms: u32, delay_ms: u32 },
+}
+
+impl Default for WakeUp {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
+/// Placeholder for a client without a wake-up pin. Setting it is a no-op.
+#[derive(Debug, Default)]
+pub struct NoPin;
+
+impl OutputPin for NoPin {
+    type Error = core::convert::Infallible;
+
+    fn try_set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn try_set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
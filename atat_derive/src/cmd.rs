@@ -1,19 +1,108 @@
 use crate::proc_macro::TokenStream;
 
-use quote::{format_ident, quote};
-use syn::parse_macro_input;
+use quote::quote;
+use syn::{parse_macro_input, GenericParam, Generics, Ident};
 
-use crate::parse::{CmdAttributes, ParseInput};
+use crate::helpers::{check_value_format, field_member, int_format, resp_lifetime};
+use crate::parse::{ArgAttributes, CmdAttributes, CmdKind, CompanionCmd, ParseInput, Variant};
 
 pub fn atat_cmd(input: TokenStream) -> TokenStream {
     let ParseInput {
         ident,
+        vis,
         at_cmd,
         generics,
         variants,
         ..
     } = parse_macro_input!(input as ParseInput);
 
+    let at_cmd = at_cmd.expect("missing #[at_cmd(...)] attribute");
+
+    let (_, takes_args) = at_cmd.kind.suffix();
+    if !takes_args && at_cmd.kind != CmdKind::Exec && !variants.is_empty() {
+        return syn::Error::new(
+            ident.span(),
+            format!(
+                "commands of kind \"{}\" cannot have any arguments",
+                at_cmd.kind.as_str()
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // The length of the command is a const argument of `AtatCmd`, which can't
+    // depend on type or const parameters
+    if let Some(param) = generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
+        return syn::Error::new_spanned(
+            param,
+            "commands can only be generic over lifetimes, as the command length \
+             must be a constant. Use a fixed capacity instead, eg. `String<64>`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let companions = [
+        (at_cmd.read.clone(), CmdKind::Read),
+        (at_cmd.test.clone(), CmdKind::Test),
+    ]
+    .iter()
+    .filter_map(|(companion, kind)| {
+        let CompanionCmd {
+            ident: companion_ident,
+            resp,
+        } = companion.as_ref()?;
+
+        let doc = format!(
+            "{} form of [`{}`], generated by `#[derive(AtatCmd)]`",
+            kind.as_str(),
+            ident
+        );
+        let companion_impl = cmd_impl(
+            companion_ident,
+            &Generics::default(),
+            &CmdAttributes {
+                resp: resp.clone(),
+                kind: *kind,
+                read: None,
+                test: None,
+                ..at_cmd.clone()
+            },
+            Vec::new(),
+        );
+
+        Some(quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #vis struct #companion_ident;
+
+            #companion_impl
+        })
+    })
+    .collect::<Vec<_>>();
+
+    let cmd_impl = cmd_impl(&ident, &generics, &at_cmd, variants);
+
+    TokenStream::from(quote! {
+        #cmd_impl
+
+        #(#companions)*
+    })
+}
+
+/// Implement `AtatLen`, `AtatPrefixLen`, `AtatCmd` and `Serialize` for the
+/// command `ident`
+fn cmd_impl(
+    ident: &Ident,
+    generics: &Generics,
+    at_cmd: &CmdAttributes,
+    variants: Vec<Variant>,
+) -> proc_macro2::TokenStream {
     let CmdAttributes {
         cmd,
         resp,
@@ -24,7 +113,16 @@ pub fn atat_cmd(input: TokenStream) -> TokenStream {
         value_sep,
         cmd_prefix,
         termination,
-    } = at_cmd.expect("missing #[at_cmd(...)] attribute");
+        kind,
+        ..
+    } = at_cmd;
+
+    // Responses are prefixed by the command and `: `, eg. `+CUN: 1`
+    let prefix_len = if cmd.is_empty() { 0 } else { cmd.len() + 2 };
+
+    let (suffix, takes_args) = kind.suffix();
+    let cmd = format!("{}{}", cmd, suffix);
+    let value_sep = *value_sep && takes_args;
 
     let ident_str = ident.to_string();
 
@@ -65,60 +163,108 @@ pub fn atat_cmd(input: TokenStream) -> TokenStream {
         None => quote! {},
     };
 
-    let subcmd_len_ident = format_ident!("U{}", cmd.len());
     let mut cmd_len = cmd_prefix.len() + cmd.len() + termination.len();
     if value_sep {
         cmd_len += 1;
     }
 
-    let cmd_len_ident = format_ident!("U{}", cmd_len);
-    let err = error.unwrap_or_else(|| syn::parse_str("atat::GenericError").unwrap());
+    // Const arguments can't depend on generic parameters, so the length is
+    // taken from the command with its lifetimes set to `'static`
+    let static_lifetimes = generics.lifetimes().map(|_| quote! { 'static });
+    let len = quote! {
+        { <#ident<#(#static_lifetimes),*> as atat::AtatLen>::LEN + #cmd_len }
+    };
+    let err = error
+        .clone()
+        .unwrap_or_else(|| syn::parse_str("atat::GenericError").unwrap());
 
-    let (field_names, field_names_str): (Vec<_>, Vec<_>) = variants
+    let (field_names, field_names_str): (Vec<_>, Vec<_>) =
+        variants.iter().map(field_member).unzip();
+    let field_values: Vec<_> = variants
         .iter()
-        .map(|f| {
-            let ident = f.ident.clone().unwrap();
-            (ident.clone(), ident.to_string())
+        .zip(field_names.iter())
+        .map(|(field, name)| {
+            check_value_format(field);
+            match field.attrs.at_arg {
+                Some(ArgAttributes {
+                    precision: Some(precision),
+                    ..
+                }) => quote! { &atat::serde_at::Fixed(&self.#name, #precision) },
+                Some(ArgAttributes {
+                    escape: Some(ref scheme),
+                    ..
+                }) => quote! { &atat::serde_at::Escaped(&self.#name, #scheme) },
+                _ => match int_format(field) {
+                    Some(format) => quote! { &atat::serde_at::Formatted(&self.#name, #format) },
+                    None => quote! { &self.#name },
+                },
+            }
         })
-        .unzip();
+        .collect();
+
+    let validate = crate::validate::validate(&variants);
+
+    // Responses can borrow from the response buffer, eg. `SocketData<'a>`
+    let resp_lt = resp_lifetime(resp, generics);
 
     let struct_len = crate::len::struct_len(variants, n_fields.checked_sub(1).unwrap_or(n_fields));
 
-    TokenStream::from(quote! {
+    quote! {
         #[automatically_derived]
         impl #impl_generics atat::AtatLen for #ident #ty_generics #where_clause {
-            type Len = #struct_len;
+            const LEN: usize = #struct_len;
         }
 
         #[automatically_derived]
-        impl #impl_generics atat::AtatCmd for #ident #ty_generics #where_clause {
-            type Response = #resp;
+        impl #impl_generics atat::AtatPrefixLen for #ident #ty_generics #where_clause {
+            const LEN: usize = #prefix_len;
+            type Response<#resp_lt> = #resp;
+        }
+
+        #[automatically_derived]
+        impl #impl_generics atat::AtatCmd<#len> for #ident #ty_generics #where_clause {
+            type Response<#resp_lt> = #resp;
             type Error = #err;
-            type CommandLen = <<Self as atat::AtatLen>::Len as core::ops::Add<::heapless::consts::#cmd_len_ident>>::Output;
+
+            /// Panics in debug builds if serialization fails. Release builds
+            /// log an error and return an empty command instead. Use
+            /// `try_as_bytes` to handle the error.
+            #[inline]
+            fn as_bytes(&self) -> atat::heapless::Vec<u8, #len> {
+                atat::derive::bytes_or_empty(self.try_as_bytes())
+            }
 
             #[inline]
-            fn as_bytes(&self) -> atat::heapless::Vec<u8, Self::CommandLen> {
-                let s: atat::heapless::String<::heapless::consts::#subcmd_len_ident> = atat::heapless::String::from(#cmd);
-                match atat::serde_at::to_vec(self, s, atat::serde_at::SerializeOptions {
+            fn try_as_bytes(&self) -> core::result::Result<atat::heapless::Vec<u8, #len>, atat::Error<Self::Error>> {
+                let mut buf = atat::heapless::Vec::new();
+                self.write_into(&mut buf)?;
+                Ok(buf)
+            }
+
+            #[inline]
+            fn write_into<W>(&self, writer: &mut W) -> core::result::Result<usize, atat::Error<Self::Error>>
+            where
+                W: atat::serde_at::ser::Write + ?Sized,
+            {
+                atat::serde_at::to_writer(self, #cmd, writer, atat::serde_at::SerializeOptions {
                     value_sep: #value_sep,
                     cmd_prefix: #cmd_prefix,
                     termination: #termination
-                }) {
-                    Ok(s) => s,
-                    Err(_) => panic!("Failed to serialize command")
-                }
+                }).map_err(atat::Error::from)
             }
 
             #[inline]
-            fn parse(&self, res: Result<&[u8], &atat::InternalError>) -> core::result::Result<Self::Response, atat::Error<Self::Error>> {
+            fn parse<#resp_lt>(&self, res: Result<&#resp_lt [u8], &atat::InternalError>) -> core::result::Result<Self::Response<#resp_lt>, atat::Error<Self::Error>> {
                 match res {
-                    Ok(resp) => atat::serde_at::from_slice::<#resp>(resp).map_err(|e| {
-                        atat::Error::Parse
+                    Ok(resp) => atat::serde_at::from_slice_with_context::<#resp>(resp).map_err(|e| {
+                        atat::Error::Parse(e.into())
                     }),
                     Err(e) => Err(e.into())
                 }
             }
 
+            #validate
+
             #timeout
 
             #abortable
@@ -146,12 +292,12 @@ pub fn atat_cmd(input: TokenStream) -> TokenStream {
                     atat::serde_at::serde::ser::SerializeStruct::serialize_field(
                         &mut serde_state,
                         #field_names_str,
-                        &self.#field_names,
+                        #field_values,
                     )?;
                 )*
 
                 atat::serde_at::serde::ser::SerializeStruct::end(serde_state)
             }
         }
-    })
+    }
 }
@@ -4,7 +4,7 @@ use syn::{parse_macro_input, parse_quote, Field, Fields, Ident, PathSegment, Typ
 
 use crate::{
     helpers,
-    parse::{ArgAttributes, EnumAttributes, ParseInput},
+    parse::{ArgAttributes, EnumAttributes, EnumValue, ParseInput},
 };
 
 struct AnonymousEnum {
@@ -56,6 +56,8 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
     let visitor = format_ident!("{}Visitor", ident);
     let field_visitor = format_ident!("{}FieldVisitor", ident);
     let invalid_val_err = format!("field index {} <= i < {}", 0, len);
+    let str_enum = helpers::is_str_enum(&variants);
+    let invalid_str_err = format!("a variant of enum {}", ident);
     let enum_name = format!("enum {}", ident);
 
     let mut deserialize_generics = syn::Generics::default();
@@ -95,7 +97,11 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
     for (i, variant) in variants.iter().enumerate() {
         let variant_ident = variant.ident.clone().unwrap();
         let variant_ident_str = variant_ident.to_string();
-        let val = if let Some(ArgAttributes { value: Some(v), .. }) = variant.attrs.at_arg {
+        let val = if let Some(ArgAttributes {
+            value: Some(EnumValue::Int(v)),
+            ..
+        }) = variant.attrs.at_arg
+        {
             quote! { #v }
         } else {
             quote! { #ident::#variant_ident }
@@ -103,6 +109,31 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
 
         let anon_ident = format_ident!("_Field{}", i);
 
+        if str_enum {
+            if !matches!(variant.fields, None | Some(Fields::Unit)) {
+                panic!("Only unit variants are allowed in enums with string values");
+            }
+            let (str_val, quoted) = helpers::str_value(variant);
+
+            info.identifier_match_arms.push(quote! {
+                #str_val => Ok(#anon_enum::#anon_ident)
+            });
+            info.deserialize_match_arms.push(quote! {
+                (#anon_enum::#anon_ident, __variant) => Ok(#ident::#variant_ident)
+            });
+            info.serialize_match_arms.push(if quoted {
+                quote! {
+                    #ident::#variant_ident => atat::serde_at::serde::Serializer::serialize_str(serializer, #str_val)
+                }
+            } else {
+                quote! {
+                    #ident::#variant_ident => atat::serde_at::serde::Serializer::serialize_bytes(serializer, #str_val.as_bytes())
+                }
+            });
+            info.anonymous_enum.fields.push(anon_ident);
+            continue;
+        }
+
         info.identifier_match_arms.push(quote! {
             a if a == #val as i64  => Ok(#anon_enum::#anon_ident)
         });
@@ -217,7 +248,11 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
         info.anonymous_enum.fields.push(anon_ident);
     }
 
-    let enum_len = crate::len::enum_len(&variants, &repr, &mut atat_len_generics);
+    let enum_len = if str_enum {
+        crate::len::str_enum_len(&variants)
+    } else {
+        crate::len::enum_len(&variants, &repr, &mut atat_len_generics)
+    };
 
     let Info {
         serialize_match_arms,
@@ -226,6 +261,54 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
         deserialize_match_arms,
     } = info;
 
+    // Enums with string values are identified by the parameter string, and
+    // enums with integer values by the parameter value
+    let (visit_identifier, deserialize_identifier) = if str_enum {
+        (
+            quote! {
+                #[inline]
+                fn visit_str<E>(
+                    self,
+                    value: &str,
+                ) -> core::result::Result<Self::Value, E>
+                where
+                    E: atat::serde_at::serde::de::Error,
+                {
+                    match value {
+                        #(#identifier_match_arms,)*
+                        _ => Err(atat::serde_at::serde::de::Error::invalid_value(
+                            atat::serde_at::serde::de::Unexpected::Str(value),
+                            &#invalid_str_err,
+                        )),
+                    }
+                }
+            },
+            quote! { deserialize_identifier },
+        )
+    } else {
+        (
+            quote! {
+                #[inline]
+                fn visit_i64<E>(
+                    self,
+                    value: i64,
+                ) -> core::result::Result<Self::Value, E>
+                where
+                    E: atat::serde_at::serde::de::Error,
+                {
+                    match value {
+                        #(#identifier_match_arms,)*
+                        _ => Err(atat::serde_at::serde::de::Error::invalid_value(
+                            atat::serde_at::serde::de::Unexpected::Signed(value),
+                            &#invalid_val_err,
+                        )),
+                    }
+                }
+            },
+            quote! { deserialize_i64 },
+        )
+    };
+
     let AnonymousEnum {
         ident: anon_ident,
         fields: anon_fields,
@@ -289,7 +372,7 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
 
         #[automatically_derived]
         impl #atat_len_impl_generics atat::AtatLen for #ident #atat_len_ty_generics #atat_len_where_clause {
-            type Len = #enum_len;
+            const LEN: usize = #enum_len;
         }
 
         #[automatically_derived]
@@ -325,22 +408,7 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
                     ) -> core::fmt::Result {
                         core::fmt::Formatter::write_str(formatter, "variant identifier")
                     }
-                    #[inline]
-                    fn visit_i64<E>(
-                        self,
-                        value: i64,
-                    ) -> core::result::Result<Self::Value, E>
-                    where
-                        E: atat::serde_at::serde::de::Error,
-                    {
-                        match value {
-                            #(#identifier_match_arms,)*
-                            _ => Err(atat::serde_at::serde::de::Error::invalid_value(
-                                atat::serde_at::serde::de::Unexpected::Signed(value),
-                                &#invalid_val_err,
-                            )),
-                        }
-                    }
+                    #visit_identifier
                 }
 
 
@@ -352,7 +420,7 @@ pub fn atat_enum(input: TokenStream) -> TokenStream {
                     where
                         D: atat::serde_at::serde::Deserializer<'de>,
                     {
-                        atat::serde_at::serde::Deserializer::deserialize_i64(deserializer, #field_visitor)
+                        atat::serde_at::serde::Deserializer::#deserialize_identifier(deserializer, #field_visitor)
                     }
                 }
                 struct #visitor #deserialize_impl_generics #deserialize_where_clause {
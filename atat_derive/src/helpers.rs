@@ -1,7 +1,10 @@
-use crate::parse::Variant;
-use proc_macro2::{Literal, Span, TokenStream};
+use crate::parse::{ArgAttributes, EnumValue, Variant};
+use proc_macro2::{Literal, Span, TokenStream, TokenTree};
 use quote::{format_ident, quote};
-use syn::{parse_quote, GenericParam, Generics, Ident, Lifetime, LifetimeDef, TypeParamBound};
+use syn::{
+    parse_quote, GenericArgument, GenericParam, Generics, Ident, Index, Lifetime, LifetimeDef,
+    Member, Path, PathArguments, Type, TypeParamBound,
+};
 
 /// Adds a single lifetime symbol eg. <'a>
 #[inline]
@@ -14,6 +17,18 @@ pub fn add_lifetime(generics: &mut Generics, lifetime_symbol: &str) {
         ))));
 }
 
+/// Adds the deserializer lifetime `'de`, outliving the lifetimes already in
+/// `generics`, eg. <'a, 'de: 'a>, so borrowed fields can be deserialized
+#[inline]
+pub fn add_de_lifetime(generics: &mut Generics) {
+    let mut de = LifetimeDef::new(Lifetime::new("'de", Span::call_site()));
+    de.bounds = generics
+        .lifetimes()
+        .map(|def| def.lifetime.clone())
+        .collect();
+    generics.params.push(GenericParam::Lifetime(de));
+}
+
 /// Adds a type generic symbol eg. <T>
 #[inline]
 pub fn add_type_generic(generics: &mut Generics, type_param: syn::TypeParam) {
@@ -61,16 +76,107 @@ pub fn add_type_parameter_bound(
         .push(where_type.into());
 }
 
-pub fn deserialize_struct(ident: &Ident, variants: &[Variant], generics: &Generics) -> TokenStream {
+/// Returns the member used to access a struct field, along with its name.
+/// Fields of tuple structs are accessed by index, and named by their index eg.
+/// `"0"`
+pub fn field_member(field: &Variant) -> (Member, String) {
+    match field.ident {
+        Some(ref ident) => (Member::Named(ident.clone()), ident.to_string()),
+        None => (
+            Member::Unnamed(Index::from(field.index)),
+            field.index.to_string(),
+        ),
+    }
+}
+
+/// Panics if a field combines more than one of `precision`, `escape` and
+/// `format`/`radix`, which each select a different representation
+pub fn check_value_format(field: &Variant) {
+    if let Some(ref arg) = field.attrs.at_arg {
+        let formats = [
+            arg.precision.map(|_| "precision"),
+            arg.escape.as_ref().map(|_| "escape"),
+            arg.radix.map(|_| "format"),
+        ];
+        let mut formats = formats.iter().flatten();
+        if let (Some(first), Some(second)) = (formats.next(), formats.next()) {
+            panic!(
+                "field `{}` cannot have both `{}` and `{}`",
+                field_member(field).1,
+                first,
+                second
+            );
+        }
+    }
+}
+
+/// Returns the `IntFormat` of a field with `#[at_arg(format = ..)]` or
+/// `#[at_arg(radix = ..)]`
+pub fn int_format(field: &Variant) -> Option<TokenStream> {
+    match field.attrs.at_arg {
+        Some(ArgAttributes {
+            radix: Some(radix),
+            width,
+            upper_case,
+            quoted,
+            ..
+        }) => {
+            let width = width.unwrap_or(0);
+            let quoted = quoted.unwrap_or(false);
+            Some(quote! {
+                atat::serde_at::IntFormat::new(#radix)
+                    .width(#width)
+                    .upper_case(#upper_case)
+                    .quoted(#quoted)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if any variant of an enum has a string value, eg.
+/// `#[at_arg(value = "SIM PIN")]`
+pub fn is_str_enum(variants: &[Variant]) -> bool {
+    variants.iter().any(|variant| {
+        matches!(
+            variant.attrs.at_arg,
+            Some(ArgAttributes {
+                value: Some(EnumValue::Str(_)),
+                ..
+            })
+        )
+    })
+}
+
+/// Returns the string value of a variant in an enum with string values, and
+/// whether it is quoted. Variants without a value use their name.
+pub fn str_value(variant: &Variant) -> (String, bool) {
+    let ident = variant.ident.as_ref().unwrap();
+    match variant.attrs.at_arg {
+        Some(ArgAttributes {
+            value: Some(EnumValue::Int(_)),
+            ..
+        }) => panic!("Cannot mix integer and string values in enum variants"),
+        Some(ArgAttributes {
+            value: Some(EnumValue::Str(ref value)),
+            quoted,
+            ..
+        }) => (value.clone(), quoted.unwrap_or(true)),
+        Some(ArgAttributes { quoted, .. }) => (ident.to_string(), quoted.unwrap_or(true)),
+        None => (ident.to_string(), true),
+    }
+}
+
+pub fn deserialize_struct(
+    ident: &Ident,
+    variants: &[Variant],
+    generics: &Generics,
+    ignore_trailing: bool,
+) -> TokenStream {
     let ident_str = ident.to_string();
 
-    let (field_names, field_names_str): (Vec<_>, Vec<_>) = variants
-        .iter()
-        .map(|f| {
-            let ident = f.ident.clone().unwrap();
-            (ident.clone(), ident.to_string())
-        })
-        .unzip();
+    let (field_names, field_names_str): (Vec<_>, Vec<_>) =
+        variants.iter().map(field_member).unzip();
     let field_types: Vec<_> = variants.iter().map(|f| f.ty.clone()).collect();
 
     let (anon_field_ind, anon_field): (Vec<usize>, Vec<Ident>) = field_names
@@ -92,9 +198,76 @@ pub fn deserialize_struct(ident: &Ident, variants: &[Variant], generics: &Generi
     let invalid_val_err = format!("field index {} <= i < {}", 0, len);
     let struct_name = format!("struct {}", ident);
 
+    // Fields marked `#[at_arg(default)]` fall back to their default value,
+    // if the parameter is empty or missing
+    let next_elements: Vec<_> = variants
+        .iter()
+        .zip(anon_field.iter())
+        .enumerate()
+        .map(|(i, (variant, anon_field))| {
+            check_value_format(variant);
+            let ty = &variant.ty;
+            let default = matches!(
+                variant.attrs.at_arg,
+                Some(ArgAttributes { default: true, .. })
+            );
+            let (ty, unwrap) = match variant.attrs.at_arg {
+                Some(ArgAttributes {
+                    escape: Some(ref scheme),
+                    ..
+                }) => (
+                    quote! { atat::serde_at::Escaped<#ty, #scheme> },
+                    quote! { .map(|escaped| escaped.0) },
+                ),
+                _ => (quote! { #ty }, quote! {}),
+            };
+            let ty = if default {
+                quote! { Option<#ty> }
+            } else {
+                quote! { #ty }
+            };
+            let next_element = match int_format(variant) {
+                Some(format) => quote! {
+                    atat::serde_at::serde::de::SeqAccess::next_element_seed(
+                        &mut seq,
+                        atat::serde_at::RadixSeed::<#ty>::new(#format),
+                    )?
+                },
+                None => quote! {
+                    atat::serde_at::serde::de::SeqAccess::next_element::<#ty>(&mut seq)?
+                },
+            };
+            if default {
+                quote! {
+                    let #anon_field = #next_element.flatten()#unwrap.unwrap_or_default();
+                }
+            } else {
+                quote! {
+                    let #anon_field = #next_element#unwrap.ok_or_else(||atat::serde_at::serde::de::Error::invalid_length(
+                        #i,
+                        &#invalid_len_err,
+                    ))?;
+                }
+            }
+        })
+        .collect();
+
+    // Skip any parameters following the last field
+    let skip_trailing = if ignore_trailing {
+        quote! {
+            while atat::serde_at::serde::de::SeqAccess::next_element::<
+                atat::serde_at::serde::de::IgnoredAny,
+            >(&mut seq)?
+            .is_some()
+            {}
+        }
+    } else {
+        quote! {}
+    };
+
     let (_, ty_generics, _) = generics.split_for_impl();
     let mut serde_generics = generics.clone();
-    add_lifetime(&mut serde_generics, "'de");
+    add_de_lifetime(&mut serde_generics);
     let (serde_impl_generics, serde_ty_generics, _) = serde_generics.split_for_impl();
 
     quote! {
@@ -206,12 +379,9 @@ pub fn deserialize_struct(ident: &Ident, variants: &[Variant], generics: &Generi
                 A: atat::serde_at::serde::de::SeqAccess<'de>,
             {
                 #(
-                    let #anon_field =
-                        atat::serde_at::serde::de::SeqAccess::next_element::<#field_types>(&mut seq)?.ok_or_else(||atat::serde_at::serde::de::Error::invalid_length(
-                            #anon_field_ind,
-                            &#invalid_len_err,
-                        ))?;
+                    #next_elements
                 )*
+                #skip_trailing
                 Ok(#ident {
                     #(
                         #field_names: #anon_field
@@ -276,3 +446,334 @@ pub fn deserialize_struct(ident: &Ident, variants: &[Variant], generics: &Generi
         )
     }
 }
+
+/// Returns true if the fields of a response are bound to response lines
+/// through `#[at_arg(prefix = "..")]`
+pub fn is_lines_struct(variants: &[Variant]) -> bool {
+    let prefixed = variants
+        .iter()
+        .filter(|variant| {
+            matches!(
+                variant.attrs.at_arg,
+                Some(ArgAttributes {
+                    prefix: Some(_),
+                    ..
+                })
+            )
+        })
+        .count();
+    if prefixed > 0 && prefixed < variants.len() {
+        panic!("Either all or no fields of a response can have a prefix");
+    }
+    prefixed > 0
+}
+
+/// Returns the first generic argument of `ty` if it is the type `name`, eg.
+/// `T` of `Option<T>`
+pub fn generic_arg<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(p) => p.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == name => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the lifetime the response type `resp` borrows from the response
+/// buffer, the one lifetime in `resp` that isn't declared by `generics`, eg.
+/// `'a` of `SocketData<'a>`. Responses that don't borrow get `'resp`.
+pub fn resp_lifetime(resp: &Path, generics: &Generics) -> Lifetime {
+    fn lifetimes(tokens: TokenStream, found: &mut Vec<Lifetime>) {
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Group(group) => lifetimes(group.stream(), found),
+                TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                    if let Some(TokenTree::Ident(ident)) = tokens.next() {
+                        found.push(Lifetime::new(&format!("'{}", ident), ident.span()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    lifetimes(quote! { #resp }, &mut found);
+    found.retain(|lt| lt.ident != "static" && !generics.lifetimes().any(|def| def.lifetime == *lt));
+    found.dedup();
+
+    match found.as_slice() {
+        [] => Lifetime::new("'resp", Span::call_site()),
+        [lt] => lt.clone(),
+        _ => panic!("responses can borrow from the response buffer with at most one lifetime"),
+    }
+}
+
+/// Deserialize a response where each field is bound to a response line by its
+/// prefix. `Option<T>` fields are optional lines, and `heapless::Vec<T, N>`
+/// fields are repeated lines.
+pub fn deserialize_lines(ident: &Ident, variants: &[Variant], generics: &Generics) -> TokenStream {
+    let (field_names, field_names_str): (Vec<_>, Vec<_>) =
+        variants.iter().map(field_member).unzip();
+    let anon_field: Vec<_> = (0..variants.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let visitor = format_ident!("{}Visitor", ident);
+    let struct_name = format!("struct {}", ident);
+
+    let mut inits = Vec::new();
+    let mut match_arms = Vec::new();
+    let mut finalize = Vec::new();
+    for ((variant, anon_field), field_name_str) in variants
+        .iter()
+        .zip(anon_field.iter())
+        .zip(field_names_str.iter())
+    {
+        let ty = variant.ty.as_ref().unwrap();
+        let prefix = match variant.attrs.at_arg {
+            Some(ArgAttributes {
+                prefix: Some(ref prefix),
+                ..
+            }) => prefix,
+            _ => unreachable!(),
+        };
+
+        if let Some(elem) = generic_arg(ty, "Vec") {
+            let invalid_len_err = format!("the capacity of field {}", field_name_str);
+            inits.push(quote! {
+                let mut #anon_field: #ty = Default::default();
+            });
+            match_arms.push(quote! {
+                #prefix => {
+                    let line = atat::serde_at::serde::de::MapAccess::next_value::<#elem>(&mut map)?;
+                    #anon_field.push(line).map_err(|_| {
+                        <A::Error as atat::serde_at::serde::de::Error>::invalid_length(
+                            #anon_field.len() + 1,
+                            &#invalid_len_err,
+                        )
+                    })?;
+                }
+            });
+        } else {
+            let (elem, missing) = match generic_arg(ty, "Option") {
+                Some(elem) => (elem, quote! {}),
+                None => (
+                    ty,
+                    quote! {
+                        let #anon_field = #anon_field.ok_or_else(|| {
+                            <A::Error as atat::serde_at::serde::de::Error>::missing_field(#field_name_str)
+                        })?;
+                    },
+                ),
+            };
+            inits.push(quote! {
+                let mut #anon_field: Option<#elem> = None;
+            });
+            match_arms.push(quote! {
+                #prefix => {
+                    if Option::is_some(&#anon_field) {
+                        return Err(
+                            <A::Error as atat::serde_at::serde::de::Error>::duplicate_field(
+                                #field_name_str,
+                            ),
+                        );
+                    }
+                    #anon_field = Some(
+                        atat::serde_at::serde::de::MapAccess::next_value::<#elem>(&mut map)?
+                    );
+                }
+            });
+            finalize.push(missing);
+        }
+    }
+
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut serde_generics = generics.clone();
+    add_de_lifetime(&mut serde_generics);
+    let (serde_impl_generics, serde_ty_generics, _) = serde_generics.split_for_impl();
+
+    quote! {
+        struct #visitor #serde_impl_generics {
+            marker: core::marker::PhantomData<#ident #ty_generics>,
+            lifetime: core::marker::PhantomData<&'de ()>,
+        }
+        impl #serde_impl_generics atat::serde_at::serde::de::Visitor<'de> for #visitor #serde_ty_generics {
+            type Value = #ident #ty_generics;
+            fn expecting(
+                &self,
+                formatter: &mut core::fmt::Formatter,
+            ) -> core::fmt::Result {
+                core::fmt::Formatter::write_str(formatter, #struct_name)
+            }
+            #[inline]
+            fn visit_map<A>(
+                self,
+                mut map: A,
+            ) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: atat::serde_at::serde::de::MapAccess<'de>,
+            {
+                #(#inits)*
+                // Lines with other prefixes are skipped
+                while let Some(key) =
+                    atat::serde_at::serde::de::MapAccess::next_key::<&str>(&mut map)?
+                {
+                    match key {
+                        #(#match_arms)*
+                        _ => {}
+                    }
+                }
+                #(#finalize)*
+                Ok(#ident {
+                    #(
+                        #field_names: #anon_field
+                    ),*
+                })
+            }
+        }
+        const FIELDS: &'static [&'static str] = &[#(#field_names_str),*];
+        atat::serde_at::serde::Deserializer::deserialize_struct(
+            deserializer,
+            atat::serde_at::de::LINES_NAME,
+            FIELDS,
+            #visitor {
+                marker: core::marker::PhantomData::<#ident #ty_generics>,
+                lifetime: core::marker::PhantomData,
+            },
+        )
+    }
+}
+
+/// Returns true if the fields of a response are bound to plain text response
+/// lines through `#[at_arg(line = ..)]`
+pub fn is_text_lines_struct(variants: &[Variant]) -> bool {
+    let indexed = variants
+        .iter()
+        .filter(|variant| {
+            matches!(
+                variant.attrs.at_arg,
+                Some(ArgAttributes { line: Some(_), .. })
+            )
+        })
+        .count();
+    if indexed > 0 && indexed < variants.len() {
+        panic!("Either all or no fields of a response can have a line");
+    }
+    indexed > 0
+}
+
+/// Deserialize a plain text response where each field is bound to a line by
+/// its index. `Option<T>` fields are optional lines at the end of the
+/// response.
+pub fn deserialize_text_lines(
+    ident: &Ident,
+    variants: &[Variant],
+    generics: &Generics,
+) -> TokenStream {
+    let (field_names, field_names_str): (Vec<_>, Vec<_>) =
+        variants.iter().map(field_member).unzip();
+    let anon_field: Vec<_> = (0..variants.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let visitor = format_ident!("{}Visitor", ident);
+    let struct_name = format!("struct {}", ident);
+
+    let lines: Vec<_> = variants
+        .iter()
+        .map(|variant| match variant.attrs.at_arg {
+            Some(ArgAttributes {
+                line: Some(line), ..
+            }) => line,
+            _ => unreachable!(),
+        })
+        .collect();
+    let n_lines = lines.iter().max().map_or(0, |max| max + 1);
+
+    // Lines are read in order, skipping lines without a field
+    let mut reads = Vec::new();
+    for line in 0..n_lines {
+        let mut fields = lines.iter().enumerate().filter(|(_, l)| **l == line);
+        match (fields.next(), fields.next()) {
+            (Some((i, _)), None) => {
+                let ty = variants[i].ty.as_ref().unwrap();
+                let elem = generic_arg(ty, "Option").unwrap_or(ty);
+                let anon_field = &anon_field[i];
+                reads.push(quote! {
+                    let #anon_field = atat::serde_at::serde::de::SeqAccess::next_element::<#elem>(&mut seq)?;
+                });
+            }
+            (Some(_), Some(_)) => panic!("Multiple fields cannot have the same line"),
+            (None, _) => reads.push(quote! {
+                atat::serde_at::serde::de::SeqAccess::next_element::<
+                    atat::serde_at::serde::de::IgnoredAny,
+                >(&mut seq)?;
+            }),
+        }
+    }
+
+    let finalize: Vec<_> = variants
+        .iter()
+        .zip(anon_field.iter())
+        .zip(field_names_str.iter())
+        .filter(|((variant, _), _)| generic_arg(variant.ty.as_ref().unwrap(), "Option").is_none())
+        .map(|((_, anon_field), field_name_str)| {
+            quote! {
+                let #anon_field = #anon_field.ok_or_else(|| {
+                    <A::Error as atat::serde_at::serde::de::Error>::missing_field(#field_name_str)
+                })?;
+            }
+        })
+        .collect();
+
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut serde_generics = generics.clone();
+    add_de_lifetime(&mut serde_generics);
+    let (serde_impl_generics, serde_ty_generics, _) = serde_generics.split_for_impl();
+
+    quote! {
+        struct #visitor #serde_impl_generics {
+            marker: core::marker::PhantomData<#ident #ty_generics>,
+            lifetime: core::marker::PhantomData<&'de ()>,
+        }
+        impl #serde_impl_generics atat::serde_at::serde::de::Visitor<'de> for #visitor #serde_ty_generics {
+            type Value = #ident #ty_generics;
+            fn expecting(
+                &self,
+                formatter: &mut core::fmt::Formatter,
+            ) -> core::fmt::Result {
+                core::fmt::Formatter::write_str(formatter, #struct_name)
+            }
+            #[inline]
+            fn visit_seq<A>(
+                self,
+                mut seq: A,
+            ) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: atat::serde_at::serde::de::SeqAccess<'de>,
+            {
+                #(#reads)*
+                #(#finalize)*
+                Ok(#ident {
+                    #(
+                        #field_names: #anon_field
+                    ),*
+                })
+            }
+        }
+        atat::serde_at::serde::Deserializer::deserialize_tuple_struct(
+            deserializer,
+            atat::serde_at::de::LINES_NAME,
+            #n_lines,
+            #visitor {
+                marker: core::marker::PhantomData::<#ident #ty_generics>,
+                lifetime: core::marker::PhantomData,
+            },
+        )
+    }
+}
@@ -1,7 +1,8 @@
 use crate::proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{parse_macro_input, Ident};
+use quote::quote;
+use syn::{parse_macro_input, GenericArgument, Ident, PathArguments, Type};
 
+use crate::helpers;
 use crate::parse::{parse_field_attr, ArgAttributes, FieldAttributes, ParseInput, Variant};
 
 /// Calculate the serialized length of a struct
@@ -10,21 +11,162 @@ use crate::parse::{parse_field_attr, ArgAttributes, FieldAttributes, ParseInput,
 /// types `AtatLen` implementation, allowing overwriting the max length of all
 /// types, including borrowed data
 pub fn struct_len(variants: Vec<Variant>, init_len: usize) -> proc_macro2::TokenStream {
-    let init_len_ident = format_ident!("U{}", init_len);
-    let mut struct_len = quote! { atat::heapless::consts::#init_len_ident };
-    for field in variants {
-        let len = if let Some(ArgAttributes { len: Some(len), .. }) = field.attrs.at_arg {
-            let len_ident = format_ident!("U{}", len);
-            quote! { atat::heapless::consts::#len_ident }
-        } else {
-            let ty = field.ty.unwrap();
-            quote! { <#ty as atat::AtatLen>::Len }
-        };
-        struct_len = quote! {
-            <#len as core::ops::Add<#struct_len>>::Output
-        };
+    let fields_len = variants.iter().map(field_len);
+    quote! { #init_len #(+ #fields_len)* }
+}
+
+/// Calculate the serialized length of a single field
+fn field_len(field: &Variant) -> proc_macro2::TokenStream {
+    let ty = field.ty.as_ref().unwrap();
+    let len = match field.attrs.at_arg {
+        Some(ArgAttributes { len: Some(len), .. }) => len as usize,
+        Some(ArgAttributes {
+            escape: Some(ref scheme),
+            ..
+        }) => return quote! { <atat::serde_at::Escaped<#ty, #scheme> as atat::AtatLen>::LEN },
+        Some(ArgAttributes {
+            precision: Some(precision),
+            ..
+        }) => return fixed_len(ty, precision),
+        Some(ArgAttributes {
+            radix: Some(radix),
+            width,
+            quoted,
+            ..
+        }) => radix_len(ty, radix, width, quoted.unwrap_or(false)),
+        _ => return quote! { <#ty as atat::AtatLen>::LEN },
+    };
+    quote! { #len }
+}
+
+/// Calculate the maximum length of a response, excluding the prefix of the
+/// first line. Each response line adds its prefix, a `: ` and a line
+/// termination, and repeated lines of a `heapless::Vec<T, N>` field count `N`
+/// times.
+///
+/// Returns `None` if the length is unbounded, ie. for responses ignoring
+/// trailing parameters or skipping plain text lines.
+pub fn resp_len(variants: Vec<Variant>, ignore_trailing: bool) -> Option<proc_macro2::TokenStream> {
+    if helpers::is_lines_struct(&variants) {
+        let lines_len = variants.iter().map(|field| {
+            let prefix = field
+                .attrs
+                .at_arg
+                .as_ref()
+                .and_then(|arg| arg.prefix.as_ref());
+            let line_overhead = prefix.map_or(0, String::len) + 4;
+            match vec_args(field.ty.as_ref().unwrap()) {
+                Some((elem_ty, capacity)) => {
+                    let elem_len = match field.attrs.at_arg {
+                        Some(ArgAttributes { len: Some(len), .. }) => {
+                            let len = len as usize;
+                            quote! { #len }
+                        }
+                        _ => quote! { <#elem_ty as atat::AtatLen>::LEN },
+                    };
+                    quote! { #capacity * (#elem_len + #line_overhead) }
+                }
+                None => {
+                    let len = field_len(field);
+                    quote! { (#len + #line_overhead) }
+                }
+            }
+        });
+        Some(quote! { 0 #(+ #lines_len)* })
+    } else if helpers::is_text_lines_struct(&variants) {
+        let mut lines: Vec<_> = variants
+            .iter()
+            .filter_map(|field| field.attrs.at_arg.as_ref()?.line)
+            .collect();
+        lines.sort_unstable();
+        if lines.iter().enumerate().any(|(i, &line)| i != line) {
+            return None;
+        }
+        let lines_len = variants.iter().map(field_len);
+        Some(quote! { 0 #(+ (#lines_len + 2))* })
+    } else if ignore_trailing {
+        None
+    } else {
+        let n_fields = variants.len();
+        Some(struct_len(
+            variants,
+            n_fields.checked_sub(1).unwrap_or(n_fields),
+        ))
+    }
+}
+
+/// Element type and capacity of a `heapless::Vec<T, N>`
+fn vec_args(ty: &Type) -> Option<(&Type, &GenericArgument)> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+            let elem_ty = match args.args.first()? {
+                GenericArgument::Type(ty) => ty,
+                _ => return None,
+            };
+            Some((elem_ty, args.args.iter().nth(1)?))
+        }
+        _ => None,
+    }
+}
+
+/// Calculate the serialized length of an unsigned integer field in `radix`,
+/// from the number of digits of its largest value
+fn radix_len(ty: &Type, radix: u32, width: Option<usize>, quoted: bool) -> usize {
+    let bits = int_bits(ty).unwrap_or_else(|| {
+        panic!("format and radix are only allowed on unsigned integers, or use `len`")
+    });
+    let mut max = u128::MAX >> (128 - bits);
+    let mut digits = 1;
+    while max >= u128::from(radix) {
+        max /= u128::from(radix);
+        digits += 1;
+    }
+    let quotes = if quoted { 2 } else { 0 };
+    digits.max(width.unwrap_or(0)) + quotes
+}
+
+/// Calculate the serialized length of a float field with `precision`
+/// decimals, as given by `serde_at::fixed_len`, which also rejects precisions
+/// above the maximum of the float type.
+fn fixed_len(ty: &Type, precision: usize) -> proc_macro2::TokenStream {
+    match primitive_ident(ty).as_deref() {
+        Some("f32" | "f64") => quote! { atat::serde_at::fixed_len::<#ty>(#precision) },
+        _ => panic!("precision is only allowed on floats"),
+    }
+}
+
+/// Number of bits of an unsigned integer type, or an `Option` of one
+fn int_bits(ty: &Type) -> Option<u32> {
+    match primitive_ident(ty)?.as_str() {
+        "u8" => Some(8),
+        "u16" => Some(16),
+        "u32" => Some(32),
+        "u64" | "usize" => Some(64),
+        "u128" => Some(128),
+        _ => None,
+    }
+}
+
+/// Name of a primitive type, looking through `Option`
+fn primitive_ident(ty: &Type) -> Option<String> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::None => Some(segment.ident.to_string()),
+        PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+            match args.args.first()? {
+                GenericArgument::Type(ty) => primitive_ident(ty),
+                _ => None,
+            }
+        }
+        _ => None,
     }
-    struct_len
 }
 
 /// Calculate the serialized length of an enum, as the longest of all variants
@@ -37,12 +179,11 @@ pub fn enum_len(
     repr: &Ident,
     _generics: &mut syn::Generics,
 ) -> proc_macro2::TokenStream {
-    let mut enum_len = quote! { atat::heapless::consts::U0 };
+    let mut enum_len = quote! { 0 };
     for variant in variants {
         if let Some(ref fields) = variant.fields {
-            let mut fields_len = quote! { atat::heapless::consts::U0 };
-            for field in fields {
-                let field_len = if let Ok(FieldAttributes {
+            let fields_len = fields.iter().map(|field| {
+                if let Ok(FieldAttributes {
                     at_arg:
                         Some(ArgAttributes {
                             len: Some(len),
@@ -59,22 +200,41 @@ pub fn enum_len(
                     if position.is_some() {
                         panic!("position is not allowed in this position");
                     }
-                    let len_ident = format_ident!("U{}", len);
-                    quote! { atat::heapless::consts::#len_ident }
+                    let len = len as usize;
+                    quote! { #len }
                 } else {
                     let ty = &field.ty;
-                    quote! { <#ty as atat::AtatLen>::Len }
-                };
-                fields_len = quote! {
-                    <<#field_len as core::ops::Add<#fields_len>>::Output as core::ops::Add<::heapless::consts::U1>>::Output
-                };
-            }
+                    quote! { <#ty as atat::AtatLen>::LEN }
+                }
+            });
             enum_len = quote! {
-                <#fields_len as atat::typenum::type_operators::Max<#enum_len>>::Output
+                {
+                    let fields_len = 0 #(+ #fields_len + 1)*;
+                    let enum_len = #enum_len;
+                    if fields_len > enum_len {
+                        fields_len
+                    } else {
+                        enum_len
+                    }
+                }
             };
         }
     }
-    quote! { <<#repr as atat::AtatLen>::Len as core::ops::Add<#enum_len>>::Output }
+    quote! { <#repr as atat::AtatLen>::LEN + #enum_len }
+}
+
+/// Calculate the serialized length of an enum with string values, as the
+/// longest value including any quotes
+pub fn str_enum_len(variants: &[Variant]) -> proc_macro2::TokenStream {
+    let len = variants
+        .iter()
+        .map(|variant| {
+            let (value, quoted) = helpers::str_value(variant);
+            value.len() + if quoted { 2 } else { 0 }
+        })
+        .max()
+        .unwrap_or(0);
+    quote! { #len }
 }
 
 pub fn atat_len(input: TokenStream) -> TokenStream {
@@ -94,7 +254,7 @@ pub fn atat_len(input: TokenStream) -> TokenStream {
     TokenStream::from(quote! {
         #[automatically_derived]
         impl #impl_generics atat::AtatLen for #ident #ty_generics #where_clause {
-            type Len = #struct_len;
+            const LEN: usize = #struct_len;
         }
     })
 }
@@ -46,13 +46,118 @@ mod len;
 mod parse;
 mod resp;
 mod urc;
+mod validate;
 
 use crate::proc_macro::TokenStream;
 
 /// Automatically derive [`atat::AtatResp`] trait
 ///
 /// [`atat::AtatResp`]: ../atat/trait.AtatResp.html
-#[proc_macro_derive(AtatResp, attributes(at_arg))]
+///
+/// Responses can be structs with named fields, tuple structs (eg.
+/// `struct SignalQuality(u8, u8)` for `+CSQ: 21,99`), or unit structs for
+/// responses without any parameters.
+///
+/// ### Container attribute (`#[at_resp(..)]`)
+/// Allowed options for `at_resp` are:
+/// - `ignore_trailing`: Skip any parameters following the last field, rather
+///   than failing to parse. Useful when newer module firmware appends
+///   parameters to the response. See [`serde_at::TrailingParams`] to change
+///   the handling when deserializing.
+/// - `len`: Implement [`atat::AtatLen`] for the response. See below.
+///
+/// ### Field attribute (`#[at_arg(..)]`)
+/// Allowed options for `at_arg` are:
+/// - `position`: **integer** The index of the parameter in the response
+/// - `default`: Use `Default::default()` for the field if the parameter is
+///   empty, eg. the third parameter of `+CGDCONT: 1,"IP",,,0,0`, or missing.
+///   Empty parameters are always deserialized as `None` for `Option<T>`
+///   fields.
+/// - `format`, `radix`: Parse an unsigned integer field in another radix, eg.
+///   `#[at_arg(format = "hex")]` for `"1A2B"`. See [`AtatCmd`](derive.AtatCmd.html)
+/// - `escape`: Decode escape sequences of a `heapless::String` field, eg.
+///   `#[at_arg(escape = "hex")]`. See [`AtatCmd`](derive.AtatCmd.html)
+/// - `prefix`: **string** Bind the field to the response line with this
+///   prefix, for responses made of lines with different prefixes. See below.
+/// - `line`: **integer** Bind the field to the plain text response line with
+///   this index, for responses without prefixes. See below.
+///
+/// ### Multi-line responses
+/// If the fields have a `prefix`, each field is deserialized from the line
+/// starting with that prefix followed by a `:`, regardless of the order of
+/// the lines. `Option<T>` fields are optional lines, `heapless::Vec<T, N>`
+/// fields collect repeated lines, and lines with other prefixes are skipped.
+/// Either all or no fields can have a prefix.
+///
+/// ```ignore
+/// #[derive(AtatResp)]
+/// pub struct Identification {
+///     #[at_arg(prefix = "Manufacturer")]
+///     pub manufacturer: String<16>,
+///     #[at_arg(prefix = "+CSQ")]
+///     pub signal: Option<SignalQuality>,
+///     #[at_arg(prefix = "+CGPADDR")]
+///     pub addresses: Vec<PdpAddress, 4>,
+/// }
+/// ```
+///
+/// ### Plain text responses
+/// If the fields have a `line`, each field is deserialized from the non-empty
+/// response line with that index, eg. for `ATI` or `AT+GMR`. Unquoted string
+/// fields take the whole line, including any commas. `Option<T>` fields are
+/// optional lines, and lines without a field are skipped. Either all or no
+/// fields can have a line. Responses that don't fit can be parsed manually
+/// through [`serde_at::Lines`].
+///
+/// ```ignore
+/// #[derive(AtatResp)]
+/// pub struct FirmwareVersion {
+///     #[at_arg(line = 0)]
+///     pub at: String<64>,
+///     #[at_arg(line = 1)]
+///     pub sdk: Option<String<64>>,
+/// }
+/// ```
+///
+/// [`serde_at::Lines`]: ../serde_at/de/struct.Lines.html
+///
+/// ### Borrowed responses
+/// String and byte slice fields, eg. `&'a str`, borrow from the response
+/// buffer instead of being copied. The command names the lifetime in its
+/// response type, eg. `#[at_cmd("+USORD", SocketData<'a>)]`, and the
+/// response is handled in place with [`atat::AtatClient::send_with`]:
+///
+/// ```ignore
+/// #[derive(AtatResp)]
+/// pub struct SocketData<'a> {
+///     #[at_arg(position = 0)]
+///     pub socket: u8,
+///     #[at_arg(position = 1, len = 512)]
+///     pub data: &'a str,
+/// }
+///
+/// client.send_with(&ReadSocketData { socket: 0, length: 512 }, |resp| {
+///     rx_buf.extend_from_slice(resp.data.as_bytes())
+/// })?;
+/// ```
+///
+/// [`atat::AtatClient::send_with`]: ../atat/trait.AtatClient.html#tymethod.send_with
+///
+/// ### Response length
+/// With `#[at_resp(len)]`, the derive also implements [`atat::AtatLen`] as the
+/// maximum length of the response, including the prefixes and line
+/// terminations of multi-line responses, with `len` overriding the length of a
+/// field. All field types must implement `AtatLen`, or have a `len`, eg. for
+/// borrowed strings. Responses with `ignore_trailing`, or skipping plain text
+/// lines, have no upper bound and can't implement it. Use
+/// [`atat::assert_buf_len`] to check at compile time that the responses to a
+/// command fit in the configured `BUF_LEN`.
+///
+/// [`atat::AtatLen`]: ../atat/derive/trait.AtatLen.html
+/// [`atat::assert_buf_len`]: ../atat/derive/fn.assert_buf_len.html
+///
+/// [`serde_at::TrailingParams`]: ../serde_at/de/enum.TrailingParams.html
+#[proc_macro_derive(AtatResp, attributes(at_arg, at_resp))]
 pub fn derive_atat_resp(input: TokenStream) -> TokenStream {
     resp::atat_resp(input)
 }
@@ -84,7 +189,7 @@ pub fn derive_atat_urc(input: TokenStream) -> TokenStream {
 ///
 /// Eg.
 /// ```ignore
-/// use heapless::{consts, String};
+/// use heapless::String;
 ///
 /// #[derive(AtatEnum)]
 /// pub enum LargeSizeVariations {
@@ -93,12 +198,12 @@ pub fn derive_atat_urc(input: TokenStream) -> TokenStream {
 ///     #[at_arg(value = 1)]
 ///     VariantTwo(u8),
 ///     #[at_arg(value = 2)]
-///     VariantThree(String<consts::U1024>)
+///     VariantThree(String<1024>)
 ///     #[at_arg(value = 2)]
-///     VariantFour(String<consts::U10>, String<consts::U10>, String<consts::U10>)
+///     VariantFour(String<10>, String<10>, String<10>)
 /// }
 /// ```
-/// will result in `<LargeSizeVariations as AtatLen>::Len == consts::U1026`, even for
+/// will result in `<LargeSizeVariations as AtatLen>::LEN == 1026`, even for
 /// `LargeSizeVariations::VariantOne`
 ///
 /// ### Container attribute (`#[at_enum(..)]`)
@@ -119,7 +224,27 @@ pub fn derive_atat_urc(input: TokenStream) -> TokenStream {
 /// `#[at_arg(..)]`, that can be specified o some or all of the fields.
 ///
 /// Allowed options for `at_arg` are:
-/// - `value` **integer** The value of the serialized field
+/// - `value` **integer or string** The value of the serialized field
+/// - `quoted` **bool** Whether a string value is quoted when serialized
+///   (defaults to `true`)
+///
+/// ### String values
+/// Enums where any variant has a string value, eg. `#[at_arg(value = "SIM PIN")]`,
+/// are serialized as that string, and deserialized from the string either
+/// quoted or bare, eg. `+CPIN: SIM PIN`. Variants without a value use their
+/// name. Only unit variants are allowed, and the `AtatLen` of the enum is the
+/// length of the longest value.
+///
+/// Eg.
+/// ```ignore
+/// #[derive(AtatEnum)]
+/// pub enum IpMode {
+///     #[at_arg(value = "TCP", quoted = false)]
+///     Tcp,
+///     #[at_arg(value = "UDP", quoted = false)]
+///     Udp,
+/// }
+/// ```
 #[proc_macro_derive(AtatEnum, attributes(at_enum, at_arg))]
 pub fn derive_atat_enum(input: TokenStream) -> TokenStream {
     enum_::atat_enum(input)
@@ -129,6 +254,8 @@ pub fn derive_atat_enum(input: TokenStream) -> TokenStream {
 ///
 /// [`atat::AtatCmd`]: ../atat/trait.AtatCmd.html
 ///
+/// Commands can only be generic over lifetimes, as the length of the command
+/// is a const argument of `AtatCmd`.
 ///
 /// ### Container attribute (`#[at_cmd(..)]`)
 /// The `AtatCmd` derive macro comes with a requirement of annotating the struct
@@ -156,6 +283,28 @@ pub fn derive_atat_enum(input: TokenStream) -> TokenStream {
 ///   'AT'). Can also be set to '' (empty).
 /// - `termination`: **string** Overwrite the line termination of the command
 ///   (default '\r\n'). Can also be set to '' (empty).
+/// - `kind`: **string** The V.250 form of the command, one of `"set"`
+///   (`AT+CMD=<args>`, default), `"read"` (`AT+CMD?`), `"test"` (`AT+CMD=?`)
+///   or `"exec"` (`AT+CMD`). Read and test commands cannot have any fields.
+///
+/// Additionally, companion unit structs for the read and test forms of the
+/// command can be generated, using the same command and options, through
+/// `read(<Ident>, <Response>)` and `test(<Ident>, <Response>)`:
+///
+/// ```ignore
+/// // Generates `GetFunctionality` sending `AT+CFUN?` and
+/// // `TestFunctionality` sending `AT+CFUN=?`
+/// #[derive(AtatCmd)]
+/// #[at_cmd(
+///     "+CFUN",
+///     NoResponse,
+///     read(GetFunctionality, Functionality),
+///     test(TestFunctionality, FunctionalityRange)
+/// )]
+/// pub struct SetFunctionality {
+///     pub fun: u8,
+/// }
+/// ```
 ///
 /// ### Field attribute (`#[at_arg(..)]`)
 /// The `AtatCmd` derive macro comes with an optional field attribute
@@ -166,6 +315,39 @@ pub fn derive_atat_enum(input: TokenStream) -> TokenStream {
 ///   string. (eg. for command `AT+CMD=a,b`, field `a` would have `position = 1`
 ///   and field `b` would have `position = 2`) (defaults to order of the fields
 ///   in the struct)
+/// - format: **string** Serialize an unsigned integer field in another radix,
+///   one of `"hex"`, `"oct"` or `"bin"`. `radix = N` selects any radix in the
+///   range `2..=36`. The digits can be further customized with `width = N`
+///   (leading zeros), `upper_case` and `quoted = true`, eg.
+///   `#[at_arg(format = "hex", width = 4, quoted = true)]` for `"00C3"`
+/// - precision: **integer** Serialize a float field with a fixed number of
+///   decimals, eg. `#[at_arg(precision = 6)]` for `55.671235`. Up to 7
+///   decimals for `f32` and 16 for `f64`. Floats are otherwise serialized
+///   with as many decimals as needed to represent the value.
+/// - escape: **string** Escape quotes, backslashes and control characters of
+///   a string field. One of `"hex"` (V.250 `\hh` escapes, eg. `\22` for `"`),
+///   `"backslash"` (eg. `\"`), or the path of a unit struct implementing
+///   [`serde_at::EscapeScheme`] for module specific schemes. Borrowed strings
+///   and custom schemes also require `len`, which is the escaped length.
+///   `precision`, `escape` and `format`/`radix` can't be combined on one
+///   field.
+/// - range: **string** Allowed range of the field value, eg.
+///   `#[at_arg(range = "0..=4")]`
+/// - max_len: **integer** Maximum length in bytes of a string or sequence
+///   field, eg. `#[at_arg(max_len = 32)]`
+/// - charset: **string** Character class of the allowed characters of a
+///   string field, with ranges, `\` escapes and `^` for negation, eg.
+///   `#[at_arg(charset = "[A-Za-z0-9_]")]` or `#[at_arg(charset = "[^\",]")]`
+///
+/// `range`, `max_len` and `charset` are checked by `AtatCmd::validate` before
+/// the command is sent, failing with `Error::InvalidArgument` and the name of
+/// the field. `Option<T>` fields are only checked if present.
+///
+/// [`serde_at::EscapeScheme`]: ../serde_at/escape/trait.EscapeScheme.html
+///
+/// Tuple structs are supported as well, in which case the fields are
+/// serialized in declaration order, unless reordered through `position`, eg.
+/// `struct SetFunctionality(u8, Option<ResetMode>)`.
 #[proc_macro_derive(AtatCmd, attributes(at_cmd, at_arg))]
 pub fn derive_atat_cmd(input: TokenStream) -> TokenStream {
     cmd::atat_cmd(input)
@@ -2,15 +2,17 @@ use proc_macro2::Span;
 use syn::parse::{Error, Parse, ParseStream, Parser, Result};
 use syn::{
     parenthesized, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Generics, Ident,
-    Lit, LitByteStr, Path, Type,
+    Lit, LitByteStr, NestedMeta, Path, Type, Visibility,
 };
 
 #[derive(Clone)]
 pub struct ParseInput {
     pub ident: Ident,
+    pub vis: Visibility,
     pub generics: Generics,
     pub at_cmd: Option<CmdAttributes>,
     pub at_enum: Option<EnumAttributes>,
+    pub at_resp: Option<RespAttributes>,
     pub variants: Vec<Variant>,
 }
 
@@ -26,14 +28,88 @@ pub struct CmdAttributes {
     pub value_sep: bool,
     pub cmd_prefix: String,
     pub termination: String,
+    pub kind: CmdKind,
+    pub read: Option<CompanionCmd>,
+    pub test: Option<CompanionCmd>,
 }
+
+/// The form of an AT command, as defined in V.250
+#[derive(Clone, Copy, PartialEq)]
+pub enum CmdKind {
+    /// `AT+CMD=<args>`
+    Set,
+    /// `AT+CMD?`
+    Read,
+    /// `AT+CMD=?`
+    Test,
+    /// `AT+CMD`
+    Exec,
+}
+
+impl CmdKind {
+    /// Suffix appended to the command, and whether the command takes any
+    /// arguments
+    pub fn suffix(self) -> (&'static str, bool) {
+        match self {
+            Self::Set => ("", true),
+            Self::Read => ("?", false),
+            Self::Test => ("=?", false),
+            Self::Exec => ("", false),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Set => "set",
+            Self::Read => "read",
+            Self::Test => "test",
+            Self::Exec => "exec",
+        }
+    }
+}
+
+/// Companion command type generated from `read(..)` or `test(..)` in
+/// `#[at_cmd(..)]`
+#[derive(Clone)]
+pub struct CompanionCmd {
+    pub ident: Ident,
+    pub resp: Path,
+}
+/// Value of an enum variant, set through `#[at_arg(value = ..)]`
+#[derive(Clone)]
+pub enum EnumValue {
+    Int(i64),
+    Str(String),
+}
+
 /// Parsed attributes of `#[at_arg(..)]`
 #[derive(Clone)]
 pub struct ArgAttributes {
-    pub value: Option<i64>,
+    pub value: Option<EnumValue>,
     pub position: Option<usize>,
     pub len: Option<u32>,
     pub default: bool,
+    /// Radix of an integer field, set through `format = ".."` or `radix = ..`
+    pub radix: Option<u32>,
+    pub width: Option<usize>,
+    pub upper_case: bool,
+    /// Whether the value is quoted. Defaults to unquoted integers and quoted
+    /// string enum values
+    pub quoted: Option<bool>,
+    /// Number of decimals of a float field
+    pub precision: Option<usize>,
+    /// Escape scheme of a string field
+    pub escape: Option<Path>,
+    /// Prefix of the response line bound to the field
+    pub prefix: Option<String>,
+    /// Index of the plain text response line bound to the field
+    pub line: Option<usize>,
+    /// Range of allowed values, eg. `0..=4`
+    pub range: Option<syn::ExprRange>,
+    /// Maximum length of a string or sequence
+    pub max_len: Option<usize>,
+    /// Character class of allowed characters, eg. `[A-Za-z0-9_]`
+    pub charset: Option<String>,
 }
 
 /// Parsed attributes of `#[at_urc(..)]`
@@ -42,6 +118,13 @@ pub struct UrcAttributes {
     pub code: LitByteStr,
 }
 
+/// Parsed attributes of `#[at_resp(..)]`
+#[derive(Clone, Default)]
+pub struct RespAttributes {
+    pub ignore_trailing: bool,
+    pub len: bool,
+}
+
 /// Parsed attributes of `#[at_enum(..)]`
 #[derive(Clone)]
 pub struct EnumAttributes {
@@ -59,6 +142,9 @@ pub struct FieldAttributes {
 pub struct Variant {
     /// Ident will be set on named variants, and None on unnamed variants
     pub ident: Option<Ident>,
+    /// Position of the field or variant in the declaration, regardless of any
+    /// `#[at_arg(position = ..)]` reordering
+    pub index: usize,
     /// Type of a struct variant
     pub ty: Option<Type>,
     /// Fields of an enum variant
@@ -111,6 +197,7 @@ fn sorted_variants(data: Data) -> Result<Vec<Variant>> {
                         i,
                         Variant {
                             ident: f.ident,
+                            index: i,
                             ty: Some(f.ty),
                             fields: None,
                             attrs: parse_field_attr(&f.attrs)?,
@@ -127,6 +214,7 @@ fn sorted_variants(data: Data) -> Result<Vec<Variant>> {
                     i,
                     Variant {
                         ident: Some(v.ident.clone()),
+                        index: i,
                         ty: None,
                         fields: Some(v.fields.clone()),
                         attrs: parse_field_attr(&v.attrs)?,
@@ -171,17 +259,31 @@ impl Parse for ArgAttributes {
             position: None,
             len: None,
             default: false,
+            radix: None,
+            width: None,
+            upper_case: false,
+            quoted: None,
+            precision: None,
+            escape: None,
+            prefix: None,
+            line: None,
+            range: None,
+            max_len: None,
+            charset: None,
         };
 
         while {
             match input.parse::<syn::Meta>()? {
                 syn::Meta::NameValue(name_value) if name_value.path.is_ident("value") => {
                     match name_value.lit.clone() {
-                        Lit::Int(v) => attrs.value = Some(v.base10_parse().unwrap()),
+                        Lit::Int(v) => {
+                            attrs.value = Some(EnumValue::Int(v.base10_parse().unwrap()))
+                        }
+                        Lit::Str(v) => attrs.value = Some(EnumValue::Str(v.value())),
                         _ => {
                             return Err(Error::new(
                                 Span::call_site(),
-                                "value argument must be an integer",
+                                "value argument must be an integer or a string",
                             ))
                         }
                     }
@@ -217,6 +319,165 @@ impl Parse for ArgAttributes {
                 syn::Meta::Path(path) if path.is_ident("default") => {
                     attrs.default = true;
                 }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("format") => {
+                    attrs.radix = match name_value.lit {
+                        Lit::Str(ref v) if v.value() == "hex" => Some(16),
+                        Lit::Str(ref v) if v.value() == "oct" => Some(8),
+                        Lit::Str(ref v) if v.value() == "bin" => Some(2),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "format argument must be one of \"hex\", \"oct\" or \"bin\"",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("radix") => {
+                    match name_value.lit.clone() {
+                        Lit::Int(v) if (2..=36).contains(&v.base10_parse().unwrap_or(0)) => {
+                            attrs.radix = Some(v.base10_parse().unwrap())
+                        }
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "radix argument must be an integer in the range 2..=36",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("width") => {
+                    match name_value.lit.clone() {
+                        Lit::Int(v) => attrs.width = Some(v.base10_parse().unwrap()),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "width argument must be a positive integer",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("precision") => {
+                    match name_value.lit.clone() {
+                        Lit::Int(v) => attrs.precision = Some(v.base10_parse().unwrap()),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "precision argument must be a positive integer",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("escape") => {
+                    attrs.escape = match name_value.lit {
+                        Lit::Str(ref v) if v.value() == "hex" => {
+                            Some(syn::parse_quote!(atat::serde_at::HexEscape))
+                        }
+                        Lit::Str(ref v) if v.value() == "backslash" => {
+                            Some(syn::parse_quote!(atat::serde_at::BackslashEscape))
+                        }
+                        Lit::Str(ref v) => Some(v.parse()?),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "escape argument must be \"hex\", \"backslash\" or the path of an escape scheme",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("range") => {
+                    match name_value.lit {
+                        Lit::Str(v) => attrs.range = Some(v.parse()?),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "range argument must be a string, eg. \"0..=4\"",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("max_len") => {
+                    match name_value.lit {
+                        Lit::Int(v) => attrs.max_len = Some(v.base10_parse()?),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "max_len argument must be an integer",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("charset") => {
+                    match name_value.lit {
+                        Lit::Str(v) => attrs.charset = Some(v.value()),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "charset argument must be a string, eg. \"[A-Za-z0-9_]\"",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("line") => {
+                    match name_value.lit {
+                        Lit::Int(v) => attrs.line = Some(v.base10_parse()?),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "line argument must be an integer",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("prefix") => {
+                    match name_value.lit {
+                        Lit::Str(v) => attrs.prefix = Some(v.value()),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "prefix argument must be a string",
+                            ))
+                        }
+                    }
+                }
+                syn::Meta::Path(path) if path.is_ident("upper_case") => {
+                    attrs.upper_case = true;
+                }
+                syn::Meta::Path(path) if path.is_ident("quoted") => {
+                    attrs.quoted = Some(true);
+                }
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("quoted") => {
+                    match name_value.lit {
+                        Lit::Bool(v) => attrs.quoted = Some(v.value),
+                        _ => {
+                            return Err(Error::new(
+                                Span::call_site(),
+                                "quoted argument must be a boolean",
+                            ))
+                        }
+                    }
+                }
+                _ => return Err(Error::new(Span::call_site(), "unknown argument!")),
+            }
+
+            input.parse::<syn::token::Comma>().is_ok()
+        } {}
+
+        Ok(attrs)
+    }
+}
+
+impl Parse for RespAttributes {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attrs = Self::default();
+
+        while {
+            match input.parse::<syn::Meta>()? {
+                syn::Meta::Path(path) if path.is_ident("ignore_trailing") => {
+                    attrs.ignore_trailing = true;
+                }
+                syn::Meta::Path(path) if path.is_ident("len") => {
+                    attrs.len = true;
+                }
                 _ => return Err(Error::new(Span::call_site(), "unknown argument!")),
             }
 
@@ -261,10 +522,24 @@ impl Parse for CmdAttributes {
             value_sep: true,
             cmd_prefix: String::from("AT"),
             termination: String::from("\r\n"),
+            kind: CmdKind::Set,
+            read: None,
+            test: None,
         };
 
         while input.parse::<syn::token::Comma>().is_ok() {
-            let optional = input.parse::<syn::MetaNameValue>()?;
+            let optional = match input.parse::<syn::Meta>()? {
+                syn::Meta::NameValue(name_value) => name_value,
+                syn::Meta::List(list) if list.path.is_ident("read") => {
+                    at_cmd.read = Some(CompanionCmd::from_nested(list.nested, "read")?);
+                    continue;
+                }
+                syn::Meta::List(list) if list.path.is_ident("test") => {
+                    at_cmd.test = Some(CompanionCmd::from_nested(list.nested, "test")?);
+                    continue;
+                }
+                _ => return Err(Error::new(call_site, "unknown argument!")),
+            };
             if optional.path.is_ident("timeout_ms") {
                 match optional.lit {
                     Lit::Int(v) => {
@@ -334,6 +609,20 @@ impl Parse for CmdAttributes {
                         ))
                     }
                 }
+            } else if optional.path.is_ident("kind") {
+                at_cmd.kind = match optional.lit {
+                    Lit::Str(v) => match v.value().as_str() {
+                        "set" => CmdKind::Set,
+                        "read" => CmdKind::Read,
+                        "test" => CmdKind::Test,
+                        "exec" => CmdKind::Exec,
+                        _ => return Err(Error::new(
+                            v.span(),
+                            "expected one of \"set\", \"read\", \"test\" or \"exec\" for 'kind'",
+                        )),
+                    },
+                    _ => return Err(Error::new(call_site, "expected string value for 'kind'")),
+                };
             }
         }
 
@@ -341,12 +630,41 @@ impl Parse for CmdAttributes {
     }
 }
 
+impl CompanionCmd {
+    fn from_nested(
+        nested: syn::punctuated::Punctuated<NestedMeta, syn::token::Comma>,
+        name: &str,
+    ) -> Result<Self> {
+        let mut paths = nested.into_iter().map(|n| match n {
+            NestedMeta::Meta(syn::Meta::Path(path)) => Some(path),
+            _ => None,
+        });
+
+        match (paths.next(), paths.next(), paths.next()) {
+            (Some(Some(ident)), Some(Some(resp)), None) if ident.get_ident().is_some() => {
+                Ok(Self {
+                    ident: ident.get_ident().unwrap().clone(),
+                    resp,
+                })
+            }
+            _ => Err(Error::new(
+                Span::call_site(),
+                format!(
+                    "expected a command name and a response type for '{}', eg. {}(GetCmd, CmdResponse)",
+                    name, name
+                ),
+            )),
+        }
+    }
+}
+
 impl Parse for ParseInput {
     fn parse(input: ParseStream) -> Result<Self> {
         let derive_input = DeriveInput::parse(input)?;
 
         let mut at_cmd = None;
         let mut at_enum = None;
+        let mut at_resp = None;
 
         // Parse valid container attributes
         for attr in derive_input.attrs {
@@ -366,14 +684,23 @@ impl Parse for ParseInput {
                 at_enum = Some(EnumAttributes {
                     repr: at_enum_arg.parse2(attr.tokens)?,
                 });
+            } else if attr.path.is_ident("at_resp") {
+                fn at_resp_arg(input: ParseStream) -> Result<RespAttributes> {
+                    let content;
+                    parenthesized!(content in input);
+                    content.parse()
+                }
+                at_resp = Some(at_resp_arg.parse2(attr.tokens)?);
             }
         }
 
         Ok(Self {
             ident: derive_input.ident,
+            vis: derive_input.vis,
             generics: derive_input.generics,
             at_cmd,
             at_enum,
+            at_resp,
             variants: sorted_variants(derive_input.data)?,
         })
     }
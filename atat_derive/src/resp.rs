@@ -2,27 +2,60 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse_macro_input;
 
-use crate::{helpers, parse::ParseInput};
+use crate::{
+    helpers,
+    parse::{ParseInput, RespAttributes},
+};
 
 pub fn atat_resp(input: TokenStream) -> TokenStream {
     let ParseInput {
         ident,
         generics,
         variants,
+        at_resp,
         ..
     } = parse_macro_input!(input as ParseInput);
 
+    let RespAttributes {
+        ignore_trailing,
+        len,
+    } = at_resp.unwrap_or_default();
+
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let mut serde_generics = generics.clone();
-    helpers::add_lifetime(&mut serde_generics, "'de");
+    helpers::add_de_lifetime(&mut serde_generics);
     let (serde_impl_generics, _, _) = serde_generics.split_for_impl();
 
-    let deserialize_struct = helpers::deserialize_struct(&ident, &variants, &generics);
+    let deserialize_struct = if helpers::is_lines_struct(&variants) {
+        helpers::deserialize_lines(&ident, &variants, &generics)
+    } else if helpers::is_text_lines_struct(&variants) {
+        helpers::deserialize_text_lines(&ident, &variants, &generics)
+    } else {
+        helpers::deserialize_struct(&ident, &variants, &generics, ignore_trailing)
+    };
+
+    let atat_len = if len {
+        let resp_len = crate::len::resp_len(variants, ignore_trailing).unwrap_or_else(|| {
+            panic!(
+                "`len` is not allowed on responses ignoring trailing parameters or skipping lines"
+            )
+        });
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics atat::AtatLen for #ident #ty_generics #where_clause {
+                const LEN: usize = #resp_len;
+            }
+        }
+    } else {
+        quote! {}
+    };
 
     TokenStream::from(quote! {
         #[automatically_derived]
         impl #impl_generics atat::AtatResp for #ident #ty_generics #where_clause {}
 
+        #atat_len
+
         #[automatically_derived]
         impl #serde_impl_generics atat::serde_at::serde::Deserialize<'de> for #ident #ty_generics #where_clause {
             #[inline]
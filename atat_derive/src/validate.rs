@@ -0,0 +1,128 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::helpers::{field_member, generic_arg};
+use crate::parse::{ArgAttributes, Variant};
+
+/// Parses a character class such as `[A-Za-z0-9_-]` or `[^",]`, returning
+/// whether it is negated and the character ranges it contains
+fn parse_charset(charset: &str) -> (bool, Vec<(char, char)>) {
+    let class = charset
+        .strip_prefix('[')
+        .and_then(|class| class.strip_suffix(']'))
+        .unwrap_or_else(|| panic!("charset must be a character class, eg. \"[A-Za-z0-9_]\""));
+    let (negated, class) = match class.strip_prefix('^') {
+        Some(class) => (true, class),
+        None => (false, class),
+    };
+
+    let mut chars = Vec::new();
+    let mut iter = class.chars();
+    while let Some(c) = iter.next() {
+        chars.push(match c {
+            '\\' => iter
+                .next()
+                .unwrap_or_else(|| panic!("charset cannot end with an escape")),
+            c => c,
+        });
+    }
+
+    // A `-` between two characters is a range, and otherwise a literal
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if i + 2 < chars.len() && chars[i + 1] == '-' {
+            if chars[i] > chars[i + 2] {
+                panic!("invalid charset range {}-{}", chars[i], chars[i + 2]);
+            }
+            ranges.push((chars[i], chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((chars[i], chars[i]));
+            i += 1;
+        }
+    }
+    if ranges.is_empty() {
+        panic!("charset cannot be empty");
+    }
+    (negated, ranges)
+}
+
+/// Returns the checks of a single field, against the field value `value`
+fn field_checks(attrs: &ArgAttributes) -> Vec<TokenStream> {
+    let mut checks = Vec::new();
+    if let Some(ref range) = attrs.range {
+        checks.push(quote! { (#range).contains(value) });
+    }
+    if let Some(max_len) = attrs.max_len {
+        checks.push(quote! { value.len() <= #max_len });
+    }
+    if let Some(ref charset) = attrs.charset {
+        let (negated, ranges) = parse_charset(charset);
+        let patterns = ranges.iter().map(|(start, end)| {
+            if start == end {
+                quote! { #start }
+            } else {
+                quote! { #start..=#end }
+            }
+        });
+        let matches = quote! { matches!(c, #(#patterns)|*) };
+        checks.push(if negated {
+            quote! { value.chars().all(|c| !#matches) }
+        } else {
+            quote! { value.chars().all(|c| #matches) }
+        });
+    }
+    checks
+}
+
+/// Generates `AtatCmd::validate`, checking the `range`, `max_len` and
+/// `charset` constraints of all fields. `Option<T>` fields are only checked
+/// if present.
+pub fn validate(variants: &[Variant]) -> TokenStream {
+    let checks: Vec<_> = variants
+        .iter()
+        .filter_map(|field| {
+            let checks = field_checks(field.attrs.at_arg.as_ref()?);
+            if checks.is_empty() {
+                return None;
+            }
+            let (name, name_str) = field_member(field);
+            let check = quote! {
+                if #(!(#checks))||* {
+                    return Err(atat::Error::InvalidArgument(#name_str));
+                }
+            };
+            Some(
+                if generic_arg(field.ty.as_ref().unwrap(), "Option").is_some() {
+                    quote! {
+                        if let Some(ref value) = self.#name {
+                            #check
+                        }
+                    }
+                } else {
+                    quote! {
+                        let value = &self.#name;
+                        #check
+                    }
+                },
+            )
+        })
+        .collect();
+
+    if checks.is_empty() {
+        return quote! {};
+    }
+
+    quote! {
+        #[inline]
+        fn validate(&self) -> core::result::Result<(), atat::Error<Self::Error>> {
+            #(
+                {
+                    #checks
+                }
+            )*
+            Ok(())
+        }
+    }
+}
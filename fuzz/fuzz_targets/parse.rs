@@ -0,0 +1,161 @@
+//! Feeds arbitrary bytes through `serde_at::from_slice` and the derived
+//! `AtatCmd::parse` of every derive shape. Any input must result in either a
+//! value or an error, never a panic.
+//!
+//! Run with `cargo fuzz run parse` from the repository root.
+#![no_main]
+
+use atat::atat_derive::{AtatCmd, AtatEnum, AtatResp};
+use atat::serde_at::{self, from_slice, Base64Bytes, CharVec, HexStr, ValueRange};
+use atat::AtatCmd;
+use heapless::{String, Vec};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, PartialEq, AtatEnum)]
+#[at_enum(u8)]
+enum Functionality {
+    #[at_arg(value = 0)]
+    Min,
+    #[at_arg(value = 1)]
+    Full,
+    #[at_arg(value = 4)]
+    Apm,
+}
+
+#[derive(Debug, PartialEq, AtatEnum)]
+enum PinStatus {
+    #[at_arg(value = "READY")]
+    Ready,
+    #[at_arg(value = "SIM PIN")]
+    SimPin,
+}
+
+// Only ever deserialized.
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, AtatEnum)]
+enum Param<'a> {
+    #[at_arg(value = 0)]
+    Unit,
+    #[at_arg(value = 1)]
+    Pair(u8, Option<u8>),
+    #[at_arg(value = 2)]
+    Named {
+        #[at_arg(len = 16)]
+        name: &'a str,
+    },
+}
+
+#[derive(Debug, PartialEq, AtatResp)]
+struct NoResponse;
+
+#[derive(Debug, PartialEq, AtatResp)]
+struct SignalQuality(u8, u8);
+
+#[derive(Debug, PartialEq, AtatResp)]
+struct Everything<'a> {
+    a: Option<u8>,
+    b: i64,
+    c: bool,
+    d: f32,
+    e: String<16>,
+    f: Vec<u8, 4>,
+    g: ValueRange<i16>,
+    h: CharVec<8>,
+    i: (u8, i8),
+    j: Functionality,
+    k: Param<'a>,
+    #[at_arg(default)]
+    l: u16,
+}
+
+#[derive(Debug, PartialEq, AtatResp)]
+#[at_resp(ignore_trailing)]
+struct Encoded<'a> {
+    #[at_arg(escape = "hex")]
+    ssid: String<16>,
+    #[at_arg(format = "hex")]
+    mask: u32,
+    hex: HexStr<u16>,
+    data: Base64Bytes<Vec<u8, 8>>,
+    text: &'a str,
+}
+
+#[derive(Debug, PartialEq, AtatResp)]
+struct Status {
+    #[at_arg(prefix = "+CSQ")]
+    signal: SignalQuality,
+    #[at_arg(prefix = "+CPIN")]
+    pin: Option<PinStatus>,
+    #[at_arg(prefix = "+CFUN")]
+    fun: Vec<Functionality, 2>,
+}
+
+#[derive(Debug, PartialEq, AtatResp)]
+struct FirmwareVersion {
+    #[at_arg(line = 0)]
+    at: String<32>,
+    #[at_arg(line = 1)]
+    sdk: Option<String<32>>,
+}
+
+#[derive(Debug, PartialEq, AtatCmd)]
+#[at_cmd("+CSQ", SignalQuality)]
+struct GetSignalQuality;
+
+#[derive(Debug, PartialEq, AtatCmd)]
+#[at_cmd("+UTEST", Everything<'a>)]
+struct GetEverything;
+
+#[derive(Debug, PartialEq, AtatCmd)]
+#[at_cmd("+UENC", Encoded<'a>)]
+struct GetEncoded;
+
+#[derive(Debug, PartialEq, AtatCmd)]
+#[at_cmd("+CGACT", Vec<SignalQuality, 4>)]
+struct GetRepeated;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = from_slice::<NoResponse>(data);
+    let _ = from_slice::<SignalQuality>(data);
+    let _ = from_slice::<Functionality>(data);
+    let _ = from_slice::<PinStatus>(data);
+    let _ = from_slice::<Param<'_>>(data);
+    let _ = from_slice::<Everything<'_>>(data);
+    let _ = from_slice::<Encoded<'_>>(data);
+    let _ = from_slice::<Status>(data);
+    let _ = from_slice::<FirmwareVersion>(data);
+    let _ = serde_at::from_slice_with_context::<Vec<Everything<'_>, 4>>(data);
+
+    let _ = GetSignalQuality.parse(Ok(data));
+    let _ = GetEverything.parse(Ok(data));
+    let _ = GetEncoded.parse(Ok(data));
+    let _ = GetRepeated.parse(Ok(data));
+});
+
+// `atat` logs through `defmt`, which needs a global logger to link. Logs are
+// discarded.
+mod logger {
+    use core::ptr::NonNull;
+
+    #[defmt::global_logger]
+    struct Logger;
+
+    impl defmt::Write for Logger {
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    unsafe impl defmt::Logger for Logger {
+        fn acquire() -> Option<NonNull<dyn defmt::Write>> {
+            Some(NonNull::from(&Logger as &dyn defmt::Write))
+        }
+
+        unsafe fn release(_: NonNull<dyn defmt::Write>) {}
+    }
+
+    defmt::timestamp!("");
+
+    #[export_name = "_defmt_panic"]
+    fn panic() -> ! {
+        panic!()
+    }
+}
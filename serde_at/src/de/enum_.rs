@@ -36,15 +36,7 @@ impl<'de, 'a> de::VariantAccess<'de> for VariantAccess<'a, 'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        if self
-            .de
-            .parse_whitespace()
-            .ok_or(Error::EofWhileParsingObject)?
-            == b','
-        {
-            self.de.eat_char();
-            self.de.parse_whitespace();
-        }
+        eat_separator(self.de)?;
         seed.deserialize(&mut *self.de)
     }
 
@@ -52,6 +44,7 @@ impl<'de, 'a> de::VariantAccess<'de> for VariantAccess<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        eat_separator(self.de)?;
         de::Deserializer::deserialize_seq(self.de, visitor)
     }
 
@@ -59,6 +52,16 @@ impl<'de, 'a> de::VariantAccess<'de> for VariantAccess<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        eat_separator(self.de)?;
         de::Deserializer::deserialize_struct(self.de, "", fields, visitor)
     }
 }
+
+/// Consumes the separator between a variant index and its fields
+fn eat_separator(de: &mut Deserializer<'_>) -> Result<()> {
+    if de.parse_whitespace().ok_or(Error::EofWhileParsingObject)? == b',' {
+        de.eat_char();
+        de.parse_whitespace();
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use core::str;
+
+use serde::de::{self, value::BorrowedStrDeserializer};
+
+use crate::de::{DeserializeOptions, Deserializer, Error, Result};
+
+/// Name used to detect multi-line responses, where each line is bound to a
+/// struct field by its prefix in `deserialize_struct`, or by its index in
+/// `deserialize_tuple_struct`
+#[doc(hidden)]
+pub const LINES_NAME: &str = "$serde_at::Lines";
+
+/// Returns the end of the line starting at `start`
+fn line_end(slice: &[u8], start: usize) -> usize {
+    slice[start..]
+        .iter()
+        .position(|&c| c == b'\r' || c == b'\n')
+        .map_or(slice.len(), |len| start + len)
+}
+
+/// Deserializes `seed` from `de.slice[start..end]` on its own, so a line
+/// can't be partially consumed. Errors are reported at their position in the
+/// whole response.
+fn deserialize_line<'de, T>(
+    de: &mut Deserializer<'de>,
+    (start, end): (usize, usize),
+    text_line: bool,
+    seed: T,
+) -> Result<T::Value>
+where
+    T: de::DeserializeSeed<'de>,
+{
+    let slice = de.slice;
+    let mut line_de = Deserializer::new(
+        &slice[start..end],
+        DeserializeOptions {
+            trailing_params: de.trailing_params,
+        },
+    );
+    line_de.text_line = text_line;
+    match seed.deserialize(&mut line_de).and_then(|value| {
+        line_de.end()?;
+        Ok(value)
+    }) {
+        Ok(value) => Ok(value),
+        Err(error) => {
+            de.index = start + line_de.index.min(end - start);
+            de.field = line_de.field;
+            Err(error)
+        }
+    }
+}
+
+/// Map of the lines in a response, keyed by their prefix, eg. `+CSQ` for
+/// `+CSQ: 12,99` or `Model` for `Model: SARA-R410M`. Lines without a prefix
+/// have an empty key.
+///
+/// Lines are skipped if the value isn't requested.
+#[allow(clippy::module_name_repetitions)]
+pub struct LineAccess<'a, 'b> {
+    de: &'a mut Deserializer<'b>,
+    /// Start and end of the value of the current line
+    value: (usize, usize),
+}
+
+impl<'a, 'b> LineAccess<'a, 'b> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
+        LineAccess { de, value: (0, 0) }
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for LineAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.de.parse_whitespace().is_none() {
+            return Ok(None);
+        }
+
+        let start = self.de.index;
+        let end = line_end(self.de.slice, start);
+        let line = &self.de.slice[start..end];
+        self.de.index = end;
+
+        let (key, value_start) = match line.iter().position(|&c| c == b':') {
+            Some(colon) => (&line[..colon], start + colon + 1),
+            None => (&line[..0], start),
+        };
+        self.value = (value_start, end);
+
+        let key = str::from_utf8(key).map_err(|_e| Error::InvalidUnicodeCodePoint)?;
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        deserialize_line(self.de, self.value, false, seed)
+    }
+}
+
+/// Sequence of the plain text lines in a response, eg. the response to `ATI`
+/// or `AT+GMR`. Unquoted strings extend to the end of the line.
+#[allow(clippy::module_name_repetitions)]
+pub struct LineSeqAccess<'a, 'b> {
+    de: &'a mut Deserializer<'b>,
+}
+
+impl<'a, 'b> LineSeqAccess<'a, 'b> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
+        LineSeqAccess { de }
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for LineSeqAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.de.parse_whitespace().is_none() {
+            return Ok(None);
+        }
+
+        let start = self.de.index;
+        let end = line_end(self.de.slice, start);
+        self.de.index = end;
+        deserialize_line(self.de, (start, end), true, seed).map(Some)
+    }
+}
+
+/// Iterator over the non-empty lines of a response, without line
+/// terminators and surrounding whitespace. Allows parsing responses that
+/// don't fit a derived response manually, eg. line by line using
+/// [`from_slice`](../fn.from_slice.html).
+///
+/// Example:
+/// ```
+/// use serde_at::Lines;
+///
+/// let response = b"AT version:1.1.0.0(May 11 2016 18:09:56)\r\nSDK version:1.5.4\r\n\r\n";
+/// let mut lines = Lines::new(response);
+///
+/// assert_eq!(lines.next(), Some(&b"AT version:1.1.0.0(May 11 2016 18:09:56)"[..]));
+/// assert_eq!(lines.next(), Some(&b"SDK version:1.5.4"[..]));
+/// assert_eq!(lines.next(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+    slice: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Lines<'a> {
+    pub const fn new(response: &'a [u8]) -> Self {
+        Lines {
+            slice: response,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.slice.get(self.index)?.is_ascii_whitespace() {
+            self.index += 1;
+        }
+        let start = self.index;
+        self.index = line_end(self.slice, start);
+        let mut line = &self.slice[start..self.index];
+        while let Some((last, rest)) = line.split_last() {
+            if !last.is_ascii_whitespace() {
+                break;
+            }
+            line = rest;
+        }
+        Some(line)
+    }
+}
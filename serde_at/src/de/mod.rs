@@ -9,28 +9,39 @@ use serde::{
 };
 
 use self::enum_::VariantAccess;
+use self::lines::{LineAccess, LineSeqAccess};
 use self::map::MapAccess;
-use self::seq::{SeqAccess, SeqByteAccess};
+use self::seq::{ParenSeqAccess, RangeAccess, SeqAccess, SeqByteAccess};
+use crate::escape::ESCAPED_STR_NAME;
+use crate::radix::RADIX_INT_NAME;
 
 mod enum_;
+mod lines;
 mod map;
 mod seq;
 
+pub use self::lines::Lines;
+#[doc(hidden)]
+pub use self::lines::LINES_NAME;
+
 /// Deserialization result
 pub type Result<T> = core::result::Result<T, Error>;
 
 /// Wrapper type to allow deserializing a number of chars as a char vector
 ///
+/// Note that `heapless::String` fields deserialize directly from both quoted
+/// and unquoted values, using a single byte per ASCII character.
+///
 /// Example:
 /// ```
-/// use heapless::{consts, String};
+/// use heapless::String;
 /// use serde_at::{from_str, Bytes, CharVec, SerializeOptions};
 /// use serde_derive::Deserialize;
 ///
 /// #[derive(Debug, Deserialize, PartialEq)]
 /// struct CommandStruct {
 ///     id: u8,
-///     vec: CharVec<consts::U7>,
+///     vec: CharVec<7>,
 ///     value: i32,
 /// }
 ///
@@ -45,51 +56,36 @@ pub type Result<T> = core::result::Result<T, Error>;
 /// assert_eq!(incoming, expected);
 /// ```
 #[derive(Debug, Clone, PartialEq)]
-pub struct CharVec<T: heapless::ArrayLength<char>>(pub heapless::Vec<char, T>);
+pub struct CharVec<const N: usize>(pub heapless::Vec<char, N>);
 
-impl<T> CharVec<T>
-where
-    T: heapless::ArrayLength<char>,
-{
+impl<const N: usize> CharVec<N> {
     #[must_use]
     pub fn new() -> Self {
-        Self(heapless::Vec::<char, T>::new())
+        Self(heapless::Vec::<char, N>::new())
     }
 
-    pub fn to_string(&self) -> heapless::String<T>
-    where
-        T: heapless::ArrayLength<u8>,
-    {
+    pub fn to_string(&self) -> heapless::String<N> {
         let mut str = heapless::String::new();
         for c in self.0.iter() {
-            // Ignore result here, as length of both `self.0` and `str` is `T`
+            // Ignore result here, as length of both `self.0` and `str` is `N`
             str.push(*c).ok();
         }
         str
     }
 }
-impl<T> Default for CharVec<T>
-where
-    T: heapless::ArrayLength<char>,
-{
+impl<const N: usize> Default for CharVec<N> {
     fn default() -> Self {
         Self::new()
     }
 }
-impl<'de, N> Deserialize<'de> for CharVec<N>
-where
-    N: heapless::ArrayLength<char>,
-{
+impl<'de, const N: usize> Deserialize<'de> for CharVec<N> {
     fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        struct ValueVisitor<'de, N>(core::marker::PhantomData<(&'de (), char, N)>);
+        struct ValueVisitor<'de, const N: usize>(core::marker::PhantomData<(&'de (), char)>);
 
-        impl<'de, N> de::Visitor<'de> for ValueVisitor<'de, N>
-        where
-            N: heapless::ArrayLength<char>,
-        {
+        impl<'de, const N: usize> de::Visitor<'de> for ValueVisitor<'de, N> {
             type Value = CharVec<N>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -118,9 +114,129 @@ where
     }
 }
 
+/// Inclusive range of values, as found in responses to test commands
+/// (`AT+CMD=?`), eg. `0-3`. A single value `x` is parsed as the range `x-x`.
+///
+/// Example:
+/// ```
+/// use heapless::Vec;
+/// use serde_at::{from_str, ValueRange};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct FunctionalityRange {
+///     fun: Vec<u8, 8>,
+///     rst: Vec<ValueRange<u8>, 2>,
+/// }
+///
+/// let incoming: FunctionalityRange = from_str("+CFUN: (0,1,4),(0-1)").unwrap();
+///
+/// assert_eq!(&incoming.fun[..], &[0, 1, 4]);
+/// assert_eq!(&incoming.rst[..], &[ValueRange { start: 0, end: 1 }]);
+/// assert!(incoming.rst[0].contains(&1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValueRange<T> {
+    pub start: T,
+    pub end: T,
+}
+
+/// Name used to detect `ValueRange` in `deserialize_tuple_struct`
+pub(crate) const VALUE_RANGE_NAME: &str = "$serde_at::ValueRange";
+
+impl<T> ValueRange<T>
+where
+    T: PartialOrd,
+{
+    /// Returns `true` if `value` is contained in the range
+    pub fn contains(&self, value: &T) -> bool {
+        self.start <= *value && *value <= self.end
+    }
+}
+
+impl<T> From<ValueRange<T>> for core::ops::RangeInclusive<T> {
+    fn from(range: ValueRange<T>) -> Self {
+        range.start..=range.end
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ValueRange<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ValueVisitor<'de, T>(core::marker::PhantomData<(&'de (), T)>);
+
+        impl<'de, T> de::Visitor<'de> for ValueVisitor<'de, T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = ValueRange<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a range of values")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let start = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let end = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(ValueRange { start, end })
+            }
+        }
+        deserializer.deserialize_tuple_struct(
+            VALUE_RANGE_NAME,
+            2,
+            ValueVisitor(core::marker::PhantomData),
+        )
+    }
+}
+
+/// Handling of parameters following the last field of a response.
+///
+/// Module firmware updates commonly append new parameters to existing
+/// responses. By default these make parsing fail with
+/// [`Error::TrailingCharacters`], unless the response type explicitly ignores
+/// them, eg. through `#[at_resp(ignore_trailing)]`. The handling can be
+/// changed per call through [`from_slice_with_options`].
+///
+/// [`Error::TrailingCharacters`]: enum.Error.html#variant.TrailingCharacters
+/// [`from_slice_with_options`]: fn.from_slice_with_options.html
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingParams {
+    /// Fail on trailing parameters, unless ignored by the response type
+    /// (default)
+    #[default]
+    Deny,
+    /// Skip trailing parameters for all response types
+    Ignore,
+    /// Fail on trailing parameters, even if ignored by the response type.
+    /// Useful in tests, to detect responses silently dropping parameters.
+    Strict,
+}
+
+/// Options used by the deserializer, to customize the handling of the
+/// response
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    /// Handling of parameters following the last field of a response.
+    ///
+    /// **default**: `TrailingParams::Deny`
+    pub trailing_params: TrailingParams,
+}
+
 /// This type represents all possible errors that can occur when deserializing AT Command strings
 #[allow(clippy::pub_enum_variant_names)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Error {
     /// EOF while parsing an object.
@@ -156,22 +272,47 @@ pub enum Error {
     /// AT Command string has a comma after the last value in an array or map.
     TrailingComma,
 
+    /// Expected a `,` or a `)` in a parenthesised list.
+    ExpectedListCommaOrEnd,
+
+    /// A value does not fit the capacity of its type, eg. a string longer
+    /// than its `heapless::String`, or a sequence has the wrong length.
+    InvalidLength,
+
+    /// The requested type cannot be deserialized from an AT Command string.
+    Unsupported,
+
     /// Error with a custom message that we had to discard.
     CustomError,
 
     /// Error with a custom message that was preserved.
     #[cfg(feature = "custom-error-messages")]
-    CustomErrorWithMessage(heapless::String<heapless::consts::U128>),
+    CustomErrorWithMessage(heapless::String<128>),
 }
 
 pub(crate) struct Deserializer<'b> {
     slice: &'b [u8],
     index: usize,
+    /// Name of the struct field currently being deserialized
+    field: Option<&'static str>,
+    /// Number of structs currently being deserialized
+    struct_depth: usize,
+    /// Deserializing a plain text line, where unquoted strings and ignored
+    /// values extend to the end of the line
+    text_line: bool,
+    trailing_params: TrailingParams,
 }
 
 impl<'a> Deserializer<'a> {
-    const fn new(slice: &'a [u8]) -> Deserializer<'_> {
-        Deserializer { slice, index: 0 }
+    const fn new(slice: &'a [u8], options: DeserializeOptions) -> Deserializer<'_> {
+        Deserializer {
+            slice,
+            index: 0,
+            field: None,
+            struct_depth: 0,
+            text_line: false,
+            trailing_params: options.trailing_params,
+        }
     }
 
     fn eat_char(&mut self) {
@@ -180,6 +321,7 @@ impl<'a> Deserializer<'a> {
 
     fn end(&mut self) -> Result<()> {
         match self.parse_whitespace() {
+            Some(b',') if self.trailing_params == TrailingParams::Ignore => Ok(()),
             Some(_) => Err(Error::TrailingCharacters),
             None => Ok(()),
         }
@@ -221,22 +363,110 @@ impl<'a> Deserializer<'a> {
         }
     }
 
-    fn parse_bytes(&mut self) -> Result<&'a [u8]> {
+    /// Parses an unquoted string, eg. `SIM PIN` in `+CPIN: SIM PIN`, up to the
+    /// next parameter separator, the end of a parenthesised list or the end of
+    /// the line. Plain text lines are parsed up to the end of the line.
+    /// Trailing whitespace is not part of the string.
+    fn parse_bare_str(&mut self) -> Result<&'a str> {
+        let start = self.index;
+        while let Some(c) = self.peek() {
+            match c {
+                b',' | b')' if !self.text_line => break,
+                b'\r' | b'\n' => break,
+                _ => self.eat_char(),
+            }
+        }
+        str::from_utf8(&self.slice[start..self.index])
+            .map(str::trim_end)
+            .map_err(|_e| Error::InvalidUnicodeCodePoint)
+    }
+
+    /// Parses a quoted string, where a backslash escapes the following
+    /// character, returning the string without the quotes. Escape sequences
+    /// are decoded by the caller.
+    fn parse_escaped_str(&mut self) -> Result<&'a str> {
+        match self.parse_whitespace() {
+            Some(b'"') => self.eat_char(),
+            Some(_) => return Err(Error::InvalidType),
+            None => return Err(Error::EofWhileParsingValue),
+        }
+
         let start = self.index;
         loop {
-            if let Some(c) = self.peek() {
-                if (c as char).is_alphanumeric() || (c as char).is_whitespace() {
-                    self.eat_char()
-                } else {
-                    return Err(Error::EofWhileParsingString);
+            match self.peek() {
+                Some(b'"') => {
+                    let end = self.index;
+                    self.eat_char();
+                    return str::from_utf8(&self.slice[start..end])
+                        .map_err(|_e| Error::InvalidUnicodeCodePoint);
                 }
-            } else {
-                let end = self.index;
-                return Ok(&self.slice[start..end]);
+                Some(b'\\') => {
+                    self.eat_char();
+                    if self.peek().is_some() {
+                        self.eat_char();
+                    }
+                }
+                Some(_) => self.eat_char(),
+                None => return Err(Error::EofWhileParsingString),
             }
         }
     }
 
+    /// Parses the digits of an integer in an arbitrary radix, optionally
+    /// enclosed in quotes, eg. `"1A2B"`
+    fn parse_radix_int(&mut self) -> Result<&'a str> {
+        let quoted = self.parse_whitespace() == Some(b'"');
+        if quoted {
+            self.eat_char();
+        }
+
+        let start = self.index;
+        while let Some(b'0'..=b'9') | Some(b'a'..=b'z') | Some(b'A'..=b'Z') = self.peek() {
+            self.eat_char();
+        }
+        let end = self.index;
+
+        if quoted {
+            match self.peek() {
+                Some(b'"') => self.eat_char(),
+                Some(_) => return Err(Error::InvalidNumber),
+                None => return Err(Error::EofWhileParsingString),
+            }
+        }
+        str::from_utf8(&self.slice[start..end]).map_err(|_e| Error::InvalidUnicodeCodePoint)
+    }
+
+    /// Skips a single parameter, including any parenthesised lists, up to the
+    /// next `,` or the end of the line
+    fn skip_param(&mut self) -> Result<()> {
+        if let Some(b'"') = self.parse_whitespace() {
+            self.eat_char();
+            self.parse_str()?;
+            return Ok(());
+        }
+
+        let mut depth = 0_usize;
+        loop {
+            match self.peek() {
+                Some(b'(') => depth += 1,
+                Some(b')') if depth > 0 => depth -= 1,
+                Some(b',') | Some(b')') if depth == 0 => return Ok(()),
+                Some(b'\r') | Some(b'\n') | None => return Ok(()),
+                Some(_) => {}
+            }
+            self.eat_char();
+        }
+    }
+
+    /// Skips all remaining parameters of the current response
+    fn skip_trailing_params(&mut self) -> Result<()> {
+        while let Some(b',') = self.parse_whitespace() {
+            self.eat_char();
+            self.skip_param()?;
+        }
+        Ok(())
+    }
+
     fn parse_at(&mut self) -> Result<Option<()>> {
         // If we find a '+', check if it is an AT command identifier, ending in ':'
         if let Some(b'+') = self.parse_whitespace() {
@@ -362,24 +592,34 @@ macro_rules! deserialize_signed {
 
 macro_rules! deserialize_fromstr {
     ($self:ident, $visitor:ident, $typ:ident, $visit_fn:ident, $pattern:expr) => {{
+        // Some modules quote decimal values, eg. GNSS coordinates
+        let quoted = $self.peek() == Some(b'"');
+        if quoted {
+            $self.eat_char();
+        }
+
         let start = $self.index;
-        loop {
+        while let Some(c) = $self.peek() {
+            if $pattern.contains(&c) {
+                $self.eat_char();
+            } else {
+                break;
+            }
+        }
+        let s = unsafe {
+            // already checked that it contains only ascii
+            str::from_utf8_unchecked(&$self.slice[start..$self.index])
+        };
+        let v = $typ::from_str(s).or(Err(Error::InvalidNumber))?;
+
+        if quoted {
             match $self.peek() {
-                Some(c) => {
-                    if $pattern.iter().find(|&&d| d == c).is_some() {
-                        $self.eat_char();
-                    } else {
-                        let s = unsafe {
-                            // already checked that it contains only ascii
-                            str::from_utf8_unchecked(&$self.slice[start..$self.index])
-                        };
-                        let v = $typ::from_str(s).or(Err(Error::InvalidNumber))?;
-                        return $visitor.$visit_fn(v);
-                    }
-                }
-                None => return Err(Error::EofWhileParsingNumber),
+                Some(b'"') => $self.eat_char(),
+                Some(_) => return Err(Error::InvalidNumber),
+                None => return Err(Error::EofWhileParsingString),
             }
         }
+        $visitor.$visit_fn(v)
     }};
 }
 
@@ -391,7 +631,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::Unsupported)
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
@@ -520,22 +760,17 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 self.eat_char();
                 visitor.visit_borrowed_str(self.parse_str()?)
             }
-            _ => {
-                if (peek as char).is_alphabetic() {
-                    visitor.visit_bytes(self.parse_bytes()?)
-                } else {
-                    Err(Error::InvalidType)
-                }
-            }
+            _ => visitor.visit_borrowed_str(self.parse_bare_str()?),
         }
     }
 
-    /// Unsupported. String is not available in no-std.
-    fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value>
+    /// Owned strings, eg. `heapless::String`, are copied from the borrowed
+    /// string, failing with `Error::InvalidLength` if it doesn't fit.
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
@@ -551,7 +786,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::Unsupported)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
@@ -559,31 +794,41 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         match self.parse_whitespace() {
-            Some(b'+') | None => visitor.visit_none(),
+            // An empty parameter, eg. the second one in `1,,3`
+            Some(b'+') | Some(b',') | None => visitor.visit_none(),
             Some(_) => visitor.visit_some(self),
         }
     }
 
-    /// Unsupported. Use a more specific deserialize_* method
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value>
+    /// Unit values carry no data, and consume no input
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        visitor.visit_unit()
     }
 
-    /// Unsupported. Use a more specific deserialize_* method
-    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    /// Unit structs are used for responses without any parameters, eg. a
+    /// plain `OK`, and only consume an optional AT command identifier
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.parse_at()?;
+        visitor.visit_unit()
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == RADIX_INT_NAME {
+            return visitor.visit_borrowed_str(self.parse_radix_int()?);
+        }
+        if name == ESCAPED_STR_NAME {
+            return visitor.visit_borrowed_str(self.parse_escaped_str()?);
+        }
+
         self.parse_at()?;
         visitor.visit_newtype_struct(self)
     }
@@ -592,28 +837,66 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        // Parenthesised list of values, eg. `(0,1,4)`
+        if let Some(b'(') = self.parse_whitespace() {
+            self.eat_char();
+            let value = visitor.visit_seq(ParenSeqAccess::new(self))?;
+            return match self.parse_whitespace() {
+                Some(b')') => {
+                    self.eat_char();
+                    Ok(value)
+                }
+                Some(_) => Err(Error::ExpectedListCommaOrEnd),
+                None => Err(Error::EofWhileParsingValue),
+            };
+        }
         visitor.visit_seq(SeqAccess::new(self))
     }
 
-    /// Unsupported
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    /// Tuples are comma separated values, optionally enclosed in parentheses,
+    /// eg. `1,2` or `(1,2)`
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.deserialize_seq(visitor)
     }
 
-    /// Unsupported
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
-        _len: usize,
-        _visitor: V,
+        name: &'static str,
+        len: usize,
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        if name == LINES_NAME {
+            // Any lines following the last field are ignored
+            let value = visitor.visit_seq(LineSeqAccess::new(self))?;
+            self.index = self.slice.len();
+            return Ok(value);
+        }
+
+        if name != VALUE_RANGE_NAME {
+            self.parse_at()?;
+            return self.deserialize_tuple(len, visitor);
+        }
+
+        // A single range may be enclosed in parentheses, eg. `(0-1)`
+        if let Some(b'(') = self.parse_whitespace() {
+            self.eat_char();
+            let value = visitor.visit_seq(RangeAccess::new(self))?;
+            return match self.parse_whitespace() {
+                Some(b')') => {
+                    self.eat_char();
+                    Ok(value)
+                }
+                Some(_) => Err(Error::ExpectedListCommaOrEnd),
+                None => Err(Error::EofWhileParsingValue),
+            };
+        }
+        visitor.visit_seq(RangeAccess::new(self))
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
@@ -626,13 +909,17 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == LINES_NAME {
+            return visitor.visit_map(LineAccess::new(self));
+        }
+
         self.parse_at()?;
 
         // Misuse EofWhileParsingObject here to indicate finished object in vec
@@ -643,7 +930,19 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         if self.index == self.slice.len() && self.index > 0 {
             return Err(Error::EofWhileParsingObject);
         }
-        self.deserialize_seq(visitor)
+
+        let outer_field = self.field;
+        self.struct_depth += 1;
+        let value = visitor.visit_seq(SeqAccess::with_fields(self, fields));
+        self.struct_depth -= 1;
+        let value = value?;
+        self.field = outer_field;
+
+        // Only the outermost struct represents a complete response
+        if self.struct_depth == 0 && self.trailing_params == TrailingParams::Ignore {
+            self.skip_trailing_params()?;
+        }
+        Ok(value)
     }
 
     fn deserialize_enum<V>(
@@ -666,22 +965,29 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_str(visitor)
     }
 
+    /// Skips a single parameter, including any parenthesised lists. Used to
+    /// skip trailing response parameters.
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'"' => self.deserialize_str(visitor),
-            b',' | b'}' | b']' => Err(Error::ExpectedSomeValue),
-            _ => loop {
-                match self.peek() {
-                    // The visitor is expected to be UnknownAny’s visitor, which
-                    // implements visit_unit to return its unit Ok result.
-                    Some(b',') | Some(b'}') | Some(b']') => break visitor.visit_unit(),
-                    Some(_) => self.eat_char(),
-                    None => break Err(Error::EofWhileParsingString),
-                }
-            },
+        if self.text_line {
+            self.index = self.slice.len();
+            return visitor.visit_unit();
+        }
+
+        match self.parse_whitespace() {
+            // Misuse EofWhileParsingObject here to indicate there are no more
+            // parameters in this response. See matching TODO in
+            // `deserialize_struct`.
+            None | Some(b'+') => Err(Error::EofWhileParsingObject),
+            Some(_) if self.trailing_params == TrailingParams::Strict => {
+                Err(Error::TrailingCharacters)
+            }
+            Some(_) => {
+                self.skip_param()?;
+                visitor.visit_unit()
+            }
         }
     }
 }
@@ -701,10 +1007,15 @@ impl de::Error for Error {
             use core::fmt::Write;
 
             let mut string = heapless::String::new();
-            write!(string, "{:.64}", msg).unwrap();
+            // A message that doesn't fit is truncated rather than discarded
+            write!(string, "{:.64}", msg).ok();
             Self::CustomErrorWithMessage(string)
         }
     }
+
+    fn invalid_length(_len: usize, _exp: &dyn de::Expected) -> Self {
+        Self::InvalidLength
+    }
 }
 
 impl fmt::Display for Error {
@@ -724,6 +1035,9 @@ impl fmt::Display for Error {
                 Self::InvalidNumber => "Invalid number.",
                 Self::InvalidType => "Invalid type",
                 Self::InvalidUnicodeCodePoint => "Invalid unicode code point.",
+                Self::ExpectedListCommaOrEnd => "Expected a `,` or a `)` in a list.",
+                Self::InvalidLength => "Value does not fit the capacity of its type.",
+                Self::Unsupported => "Unsupported type.",
                 Self::TrailingCharacters => {
                     "AT Command string has non-whitespace trailing characters after \
                      the \
@@ -742,15 +1056,108 @@ impl fmt::Display for Error {
 #[cfg(any(test, feature = "std"))]
 impl std::error::Error for Error {}
 
+/// An [`Error`], along with the location in the AT Response text where it
+/// occurred
+///
+/// [`Error`]: enum.Error.html
+#[derive(Debug, PartialEq)]
+pub struct ErrorContext {
+    /// The error that occurred
+    pub error: Error,
+    /// Byte offset into the input at which the error occurred
+    pub index: usize,
+    /// Name of the struct field being deserialized when the error occurred,
+    /// if any. Fields of tuple structs are named by their index, eg. `"0"`.
+    pub field: Option<&'static str>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at index {}", self.error, self.index)?;
+        if let Some(field) = self.field {
+            write!(f, ", field `{}`", field)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl std::error::Error for ErrorContext {}
+
 /// Deserializes an instance of type `T` from bytes of AT Response text
 pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
 where
     T: de::Deserialize<'a>,
 {
-    let mut de = Deserializer::new(v);
-    let value = de::Deserialize::deserialize(&mut de)?;
-    de.end()?;
-    Ok(value)
+    from_slice_with_context(v).map_err(|e| e.error)
+}
+
+/// Deserializes an instance of type `T` from bytes of AT Response text,
+/// reporting where in the input any error occurred
+///
+/// Example:
+/// ```
+/// use serde_at::{de::Error, from_slice_with_context};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Registration {
+///     n: u8,
+///     stat: u8,
+/// }
+///
+/// let err = from_slice_with_context::<Registration>(b"+CREG: 0,x").unwrap_err();
+///
+/// assert_eq!(err.error, Error::InvalidType);
+/// assert_eq!(err.index, 9);
+/// assert_eq!(err.field, Some("stat"));
+/// ```
+pub fn from_slice_with_context<'a, T>(v: &'a [u8]) -> core::result::Result<T, ErrorContext>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_options(v, DeserializeOptions::default())
+}
+
+/// Deserializes an instance of type `T` from bytes of AT Response text with
+/// `options`, reporting where in the input any error occurred
+///
+/// Example:
+/// ```
+/// use serde_at::{from_slice_with_options, DeserializeOptions, TrailingParams};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Registration {
+///     n: u8,
+///     stat: u8,
+/// }
+///
+/// let options = DeserializeOptions {
+///     trailing_params: TrailingParams::Ignore,
+/// };
+/// let reg = from_slice_with_options::<Registration>(b"+CREG: 0,1,\"4E54\"", options);
+///
+/// assert_eq!(reg, Ok(Registration { n: 0, stat: 1 }));
+/// ```
+pub fn from_slice_with_options<'a, T>(
+    v: &'a [u8],
+    options: DeserializeOptions,
+) -> core::result::Result<T, ErrorContext>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v, options);
+    de::Deserialize::deserialize(&mut de)
+        .and_then(|value| {
+            de.end()?;
+            Ok(value)
+        })
+        .map_err(|error| ErrorContext {
+            error,
+            index: de.index.min(v.len()),
+            field: de.field,
+        })
 }
 
 /// Deserializes an instance of type T from a string of AT Response text
@@ -763,8 +1170,8 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::CharVec;
-    use heapless::{consts, String};
+    use super::{CharVec, ValueRange};
+    use heapless::{String, Vec};
     use serde_derive::Deserialize;
 
     #[derive(Debug, Clone, Deserialize, PartialEq)]
@@ -832,11 +1239,96 @@ mod tests {
             })
         );
     }
+    #[test]
+    fn empty_params() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct PdpContext<'a> {
+            cid: u8,
+            pdp_type: &'a str,
+            apn: Option<&'a str>,
+            addr: Option<u8>,
+            d_comp: u8,
+            h_comp: Option<u8>,
+        }
+
+        assert_eq!(
+            crate::from_str("+CGDCONT: 1,\"IP\",,,0,0"),
+            Ok(PdpContext {
+                cid: 1,
+                pdp_type: "IP",
+                apn: None,
+                addr: None,
+                d_comp: 0,
+                h_comp: Some(0),
+            })
+        );
+        assert_eq!(
+            crate::from_str("+CGDCONT: 1,\"IP\",\"internet\",,0,"),
+            Ok(PdpContext {
+                cid: 1,
+                pdp_type: "IP",
+                apn: Some("internet"),
+                addr: None,
+                d_comp: 0,
+                h_comp: None,
+            })
+        );
+
+        assert_eq!(
+            crate::from_str("+CFG: ,56,"),
+            Ok(CFGOptionFirst {
+                p1: None,
+                p2: 56,
+                p3: None
+            })
+        );
+        assert!(crate::from_str::<CFG>("+CFG: 2,,false").is_err());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct CFGOptionFirst {
+        p1: Option<u8>,
+        p2: i16,
+        p3: Option<bool>,
+    }
+
+    #[test]
+    fn floats() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Position {
+            fix: u8,
+            lat: f64,
+            lon: f64,
+            alt: Option<f32>,
+        }
+
+        assert_eq!(
+            crate::from_str("+CGNSINF: 1,55.671234,-12.5"),
+            Ok(Position {
+                fix: 1,
+                lat: 55.671_234,
+                lon: -12.5,
+                alt: None,
+            })
+        );
+        assert_eq!(
+            crate::from_str("+CGNSINF: 1,\"55.671234\",\"-12.5\",1.2e2"),
+            Ok(Position {
+                fix: 1,
+                lat: 55.671_234,
+                lon: -12.5,
+                alt: Some(120.0),
+            })
+        );
+        assert!(crate::from_str::<Position>("+CGNSINF: 1,\"55.67,-12.5").is_err());
+        assert!(crate::from_str::<Position>("+CGNSINF: 1,N,-12.5").is_err());
+    }
+
     #[test]
     fn simple_string() {
         #[derive(Clone, Debug, Deserialize, PartialEq)]
         pub struct StringTest {
-            pub string: String<consts::U32>,
+            pub string: String<32>,
         }
 
         assert_eq!(
@@ -847,11 +1339,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bare_string() {
+        #[derive(Clone, Debug, Deserialize, PartialEq)]
+        pub struct Status<'a> {
+            pub code: &'a str,
+            pub mode: String<8>,
+            pub id: u8,
+        }
+
+        assert_eq!(
+            crate::from_str("+CPIN: SIM PIN ,TCP,2"),
+            Ok(Status {
+                code: "SIM PIN",
+                mode: String::from("TCP"),
+                id: 2
+            })
+        );
+    }
+
+    #[test]
+    fn owned_string() {
+        #[derive(Clone, Debug, Deserialize, PartialEq)]
+        pub struct Identity {
+            pub imei: String<15>,
+            pub model: String<8>,
+        }
+
+        assert_eq!(
+            crate::from_str("+CGSN: 352913090123456,\"SARA-R4\""),
+            Ok(Identity {
+                imei: String::from("352913090123456"),
+                model: String::from("SARA-R4")
+            })
+        );
+        assert_eq!(
+            crate::from_str("+CGSN: \"352913090123456\",SARA-R4"),
+            Ok(Identity {
+                imei: String::from("352913090123456"),
+                model: String::from("SARA-R4")
+            })
+        );
+        assert_eq!(
+            crate::from_str::<Identity>("+CGSN: 352913090123456,\"SARA-R410M\""),
+            Err(crate::de::Error::InvalidLength)
+        );
+    }
+
     #[test]
     fn cgmi_string() {
         #[derive(Clone, Debug, Deserialize, PartialEq)]
         pub struct CGMI {
-            pub id: CharVec<consts::U32>,
+            pub id: CharVec<32>,
         }
 
         assert_eq!(
@@ -884,17 +1423,204 @@ mod tests {
 
     #[test]
     fn char_vec_struct() {
-        assert_eq!(
-            CharVec(heapless::Vec::<char, consts::U4>::new()),
-            CharVec::new()
-        );
+        assert_eq!(CharVec(heapless::Vec::<char, 4>::new()), CharVec::new());
 
-        let res: CharVec<consts::U4> = crate::from_str("+CCID: IMP_").unwrap();
+        let res: CharVec<4> = crate::from_str("+CCID: IMP_").unwrap();
         assert_eq!(
             res,
             CharVec(heapless::Vec::from_slice(&['I', 'M', 'P', '_']).unwrap())
         );
 
-        assert_eq!(res.to_string(), String::<consts::U4>::from("IMP_"));
+        assert_eq!(res.to_string(), String::<4>::from("IMP_"));
+    }
+
+    #[test]
+    fn value_lists() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct FunctionalityRange {
+            fun: Vec<u8, 8>,
+            rst: ValueRange<u8>,
+        }
+
+        assert_eq!(
+            crate::from_str("+CFUN: (0,1,4),(0-1)"),
+            Ok(FunctionalityRange {
+                fun: Vec::from_slice(&[0, 1, 4]).unwrap(),
+                rst: ValueRange { start: 0, end: 1 },
+            })
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Mixed {
+            ranges: Vec<ValueRange<i16>, 4>,
+            names: Vec<String<8>, 4>,
+            empty: Vec<u8, 4>,
+            last: u8,
+        }
+
+        let mixed: Mixed =
+            crate::from_str("+URAT: (0-3, 5,-10--2),(\"GSM\",\"LTE\"),(),7").unwrap();
+        assert_eq!(
+            &mixed.ranges[..],
+            &[
+                ValueRange { start: 0, end: 3 },
+                ValueRange { start: 5, end: 5 },
+                ValueRange {
+                    start: -10,
+                    end: -2
+                },
+            ]
+        );
+        assert!(mixed.ranges[0].contains(&2));
+        assert!(!mixed.ranges[1].contains(&4));
+        assert_eq!(
+            &mixed.names[..],
+            &[String::<8>::from("GSM"), String::from("LTE")]
+        );
+        assert!(mixed.empty.is_empty());
+        assert_eq!(mixed.last, 7);
+
+        assert_eq!(
+            crate::from_str::<Vec<u8, 4>>("(1,2"),
+            Err(crate::de::Error::EofWhileParsingValue)
+        );
+        assert_eq!(
+            crate::from_str::<Vec<u8, 4>>("(1 2)"),
+            Err(crate::de::Error::ExpectedListCommaOrEnd)
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Signal(u8, u8);
+
+        assert_eq!(crate::from_str("+CSQ: 21,99"), Ok(Signal(21, 99)));
+        assert_eq!(crate::from_str("+CSQ: (21,99)"), Ok(Signal(21, 99)));
+        assert_eq!(
+            crate::from_str::<Signal>("+CSQ: 21"),
+            Err(crate::de::Error::EofWhileParsingValue)
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct WithTuple {
+            id: u8,
+            pair: (i16, bool),
+            last: u8,
+        }
+
+        assert_eq!(
+            crate::from_str("+CMD: 1,-2,true,3"),
+            Ok(WithTuple {
+                id: 1,
+                pair: (-2, true),
+                last: 3
+            })
+        );
+        assert_eq!(
+            crate::from_str("+CMD: 1,(-2,true),3"),
+            Ok(WithTuple {
+                id: 1,
+                pair: (-2, true),
+                last: 3
+            })
+        );
+    }
+
+    #[test]
+    fn unit() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct NoResponse;
+
+        assert_eq!(crate::from_str(""), Ok(NoResponse));
+        assert_eq!(crate::from_str(""), Ok(()));
+    }
+
+    #[test]
+    fn unsupported() {
+        struct Any;
+
+        impl<'de> serde::Deserialize<'de> for Any {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(serde::de::IgnoredAny)?;
+                Ok(Any)
+            }
+        }
+
+        assert!(matches!(
+            crate::from_str::<Any>("+CMD: 1"),
+            Err(crate::de::Error::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn error_context() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            a: u8,
+            b: bool,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            x: u8,
+            inner: Inner,
+            y: u8,
+        }
+
+        let err = crate::from_slice_with_context::<Outer>(b"+CMD: 1,2,maybe,3").unwrap_err();
+        assert_eq!(err.error, crate::de::Error::InvalidType);
+        assert_eq!(err.field, Some("b"));
+        assert_eq!(err.index, 10);
+
+        // The field of the outer struct is restored after the inner one
+        let err = crate::from_slice_with_context::<Outer>(b"+CMD: 1,2,true,-3").unwrap_err();
+        assert_eq!(err.error, crate::de::Error::InvalidNumber);
+        assert_eq!(err.field, Some("y"));
+        assert_eq!(err.index, 15);
+
+        let err = crate::from_slice_with_context::<CFG>(b"+CFG: 1,2,true x").unwrap_err();
+        assert_eq!(err.error, crate::de::Error::TrailingCharacters);
+        assert_eq!(err.field, None);
+        assert_eq!(err.index, 15);
+    }
+
+    #[test]
+    fn trailing_params() {
+        use crate::de::{DeserializeOptions, Error, TrailingParams};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Registration {
+            n: u8,
+            stat: u8,
+        }
+
+        let input = "+CREG: 1,2,3,(4,5),\"x,y\",abc";
+        let lines = "+CREG: 1,2,3,\"x\"\r\n+CREG: 4,5\r\n+CREG: 6,7,(8,9)";
+
+        assert_eq!(
+            crate::from_str::<Registration>(input),
+            Err(Error::TrailingCharacters)
+        );
+
+        let options = DeserializeOptions {
+            trailing_params: TrailingParams::Ignore,
+        };
+        let single = crate::from_slice_with_options::<Registration>(input.as_bytes(), options);
+        let multi =
+            crate::from_slice_with_options::<Vec<Registration, 4>>(lines.as_bytes(), options);
+
+        assert_eq!(single, Ok(Registration { n: 1, stat: 2 }));
+        assert_eq!(
+            &multi.unwrap()[..],
+            &[
+                Registration { n: 1, stat: 2 },
+                Registration { n: 4, stat: 5 },
+                Registration { n: 6, stat: 7 }
+            ]
+        );
     }
 }
@@ -6,11 +6,27 @@ use crate::de::{Deserializer, Error, Result};
 pub struct SeqAccess<'a, 'b> {
     first: bool,
     de: &'a mut Deserializer<'b>,
+    fields: &'static [&'static str],
+    count: usize,
 }
 
 impl<'a, 'b> SeqAccess<'a, 'b> {
     pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
-        SeqAccess { de, first: true }
+        Self::with_fields(de, &[])
+    }
+
+    /// Sequence of struct fields, keeping track of the field being
+    /// deserialized for error reporting
+    pub(crate) fn with_fields(
+        de: &'a mut Deserializer<'b>,
+        fields: &'static [&'static str],
+    ) -> Self {
+        SeqAccess {
+            de,
+            first: true,
+            fields,
+            count: 0,
+        }
     }
 }
 
@@ -22,11 +38,11 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
         T: de::DeserializeSeed<'de>,
     {
         match self.de.parse_whitespace() {
-            Some(b',') => {
+            Some(b',') if !self.first => {
+                // An empty parameter following the separator, eg. `1,,3` or
+                // `1,`, is left to `deserialize_option`
                 self.de.eat_char();
-                self.de
-                    .parse_whitespace()
-                    .ok_or(Error::EofWhileParsingValue)?;
+                self.de.parse_whitespace();
             }
             Some(c) => {
                 if self.first {
@@ -41,6 +57,11 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
             }
         };
 
+        if let Some(field) = self.fields.get(self.count) {
+            self.de.field = Some(field);
+        }
+        self.count += 1;
+
         match seed.deserialize(&mut *self.de) {
             // Misuse EofWhileParsingObject here to indicate finished object in vec cases.
             // See matching TODO in `de::mod`..
@@ -79,3 +100,78 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqByteAccess<'a, 'de> {
         Ok(Some(seed.deserialize(&mut *self.de)?))
     }
 }
+
+/// Access to the elements of a parenthesised list, eg. `(0,1,4)`. The opening
+/// and closing parentheses are handled by the deserializer.
+#[allow(clippy::module_name_repetitions)]
+pub struct ParenSeqAccess<'a, 'b> {
+    first: bool,
+    de: &'a mut Deserializer<'b>,
+}
+
+impl<'a, 'b> ParenSeqAccess<'a, 'b> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
+        ParenSeqAccess { de, first: true }
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for ParenSeqAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.de.parse_whitespace() {
+            Some(b')') => return Ok(None),
+            Some(b',') if !self.first => {
+                self.de.eat_char();
+            }
+            Some(_) if self.first => {}
+            Some(_) => return Err(Error::ExpectedListCommaOrEnd),
+            None => return Err(Error::EofWhileParsingValue),
+        };
+        self.first = false;
+
+        Ok(Some(seed.deserialize(&mut *self.de)?))
+    }
+}
+
+/// Access to the start and end of a range of values, eg. `0-3`. A single value
+/// is used as both start and end.
+pub struct RangeAccess<'a, 'b> {
+    start: Option<usize>,
+    de: &'a mut Deserializer<'b>,
+}
+
+impl<'a, 'b> RangeAccess<'a, 'b> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
+        RangeAccess { de, start: None }
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for RangeAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.start {
+            None => {
+                self.de.parse_whitespace();
+                self.start = Some(self.de.index);
+            }
+            Some(start) => {
+                if let Some(b'-') = self.de.parse_whitespace() {
+                    self.de.eat_char();
+                } else {
+                    // Single value, deserialize it again as the end of the range
+                    self.de.index = start;
+                }
+            }
+        }
+
+        Ok(Some(seed.deserialize(&mut *self.de)?))
+    }
+}
@@ -0,0 +1,255 @@
+//! Binary data encoded as hexadecimal or base64 string parameters, eg. for
+//! certificate uploads, MQTT payloads or socket data
+
+use core::fmt::{self, Write};
+
+use heapless::Vec;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Bytes encoded as a quoted string of two hexadecimal digits per byte, eg.
+/// `"48656C6C6F"` for `b"Hello"`.
+///
+/// Serializes any bytes, eg. `&[u8]` or `heapless::Vec<u8, N>`, and
+/// deserializes into a `heapless::Vec<u8, N>`, accepting both upper and lower
+/// case digits.
+///
+/// Example:
+/// ```
+/// use heapless::{String, Vec};
+/// use serde_at::{from_str, to_string, HexBytes, SerializeOptions};
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct SocketData {
+///     socket: u8,
+///     data: HexBytes<Vec<u8, 16>>,
+/// }
+///
+/// let data = SocketData {
+///     socket: 1,
+///     data: HexBytes(Vec::from_slice(b"Hello").unwrap()),
+/// };
+/// let s: String<64> = to_string(
+///     &data,
+///     String::<32>::from("+USOWR"),
+///     SerializeOptions::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, String::<64>::from("AT+USOWR=1,\"48656C6C6F\"\r\n"));
+///
+/// let incoming: SocketData = from_str("+USORD: 1,\"48656c6c6f\"").unwrap();
+/// assert_eq!(incoming, data);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HexBytes<T>(pub T);
+
+/// Bytes encoded as a quoted, padded base64 string using the standard
+/// alphabet, eg. `"SGVsbG8="` for `b"Hello"`.
+///
+/// Serializes any bytes, eg. `&[u8]` or `heapless::Vec<u8, N>`, and
+/// deserializes into a `heapless::Vec<u8, N>`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Base64Bytes<T>(pub T);
+
+impl<T> fmt::Display for HexBytes<T>
+where
+    T: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0.as_ref() {
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> fmt::Display for Base64Bytes<T>
+where
+    T: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.0.as_ref().chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0_u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let sextet = (n >> (18 - 6 * i)) & 0x3F;
+                    f.write_char(BASE64_ALPHABET[sextet as usize] as char)?;
+                } else {
+                    f.write_char('=')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_serialize {
+    ($wrapper:ident) => {
+        impl<T> Serialize for $wrapper<T>
+        where
+            T: AsRef<[u8]>,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+    };
+}
+
+impl_serialize!(HexBytes);
+impl_serialize!(Base64Bytes);
+
+fn decode_hex<const N: usize>(v: &str) -> Option<Result<Vec<u8, N>, ()>> {
+    let pairs = v.as_bytes().chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    for pair in pairs {
+        let high = (pair[0] as char).to_digit(16)?;
+        let low = (pair[1] as char).to_digit(16)?;
+        if bytes.push((high << 4 | low) as u8).is_err() {
+            return Some(Err(()));
+        }
+    }
+    Some(Ok(bytes))
+}
+
+fn decode_base64<const N: usize>(v: &str) -> Option<Result<Vec<u8, N>, ()>> {
+    let chunks = v.as_bytes().chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    let n_chunks = chunks.len();
+    for (i, chunk) in chunks.enumerate() {
+        // Padding is only allowed at the end of the last chunk
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != n_chunks) {
+            return None;
+        }
+        let mut n = 0_u32;
+        for &c in &chunk[..4 - padding] {
+            let sextet = BASE64_ALPHABET.iter().position(|&a| a == c)?;
+            n = n << 6 | sextet as u32;
+        }
+        n <<= 6 * padding;
+        let decoded = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        if bytes.extend_from_slice(&decoded[..3 - padding]).is_err() {
+            return Some(Err(()));
+        }
+    }
+    Some(Ok(bytes))
+}
+
+macro_rules! impl_deserialize {
+    ($wrapper:ident, $decode:ident, $expecting:expr) => {
+        impl<'de, const N: usize> Deserialize<'de> for $wrapper<Vec<u8, N>> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct BytesVisitor<const N: usize>;
+
+                impl<'de, const N: usize> de::Visitor<'de> for BytesVisitor<N> {
+                    type Value = Vec<u8, N>;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        formatter.write_str($expecting)
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                    where
+                        E: de::Error,
+                    {
+                        match $decode(v) {
+                            Some(Ok(bytes)) => Ok(bytes),
+                            Some(Err(())) => Err(E::invalid_length(v.len(), &self)),
+                            None => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_str(BytesVisitor).map($wrapper)
+            }
+        }
+    };
+}
+
+impl_deserialize!(HexBytes, decode_hex, "a hexadecimal string");
+impl_deserialize!(Base64Bytes, decode_base64, "a base64 string");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_str, to_string, SerializeOptions};
+    use heapless::String;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Publish {
+        hex: HexBytes<Vec<u8, 8>>,
+        base64: Base64Bytes<Vec<u8, 8>>,
+    }
+
+    fn publish(hex: &[u8], base64: &[u8]) -> Publish {
+        Publish {
+            hex: HexBytes(Vec::from_slice(hex).unwrap()),
+            base64: Base64Bytes(Vec::from_slice(base64).unwrap()),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for (bytes, hex, base64) in &[
+            (&b""[..], "", ""),
+            (&b"f"[..], "66", "Zg=="),
+            (&b"fo"[..], "666F", "Zm8="),
+            (&b"foo"[..], "666F6F", "Zm9v"),
+            (&b"\x00\xFF\x10\xFE"[..], "00FF10FE", "AP8Q/g=="),
+        ] {
+            let publish = publish(bytes, bytes);
+            let s: String<64> = to_string(
+                &publish,
+                String::<8>::from("+PUB"),
+                SerializeOptions::default(),
+            )
+            .unwrap();
+            let expected = format!("AT+PUB=\"{}\",\"{}\"\r\n", hex, base64);
+            assert_eq!(s.as_str(), expected.as_str());
+
+            let incoming = format!("+PUB: \"{}\",\"{}\"", hex.to_lowercase(), base64);
+            assert_eq!(from_str::<Publish>(&incoming), Ok(publish));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        // Odd number of hexadecimal digits
+        assert!(from_str::<Publish>("+PUB: \"666\",\"\"").is_err());
+        // Invalid hexadecimal digit
+        assert!(from_str::<Publish>("+PUB: \"6G\",\"\"").is_err());
+        // Missing base64 padding
+        assert!(from_str::<Publish>("+PUB: \"\",\"Zg\"").is_err());
+        // Padding before the last chunk
+        assert!(from_str::<Publish>("+PUB: \"\",\"Zg==Zm9v\"").is_err());
+        // Too long
+        assert_eq!(
+            from_str::<Publish>("+PUB: \"000102030405060708\",\"\""),
+            Err(crate::de::Error::InvalidLength)
+        );
+        assert_eq!(
+            from_str::<Publish>("+PUB: \"\",\"AAECAwQFBgcI\""),
+            Err(crate::de::Error::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Escaping of quoted string parameters, allowing strings such as SSIDs,
+//! passwords or MQTT payloads to contain quotes, backslashes or control
+//! characters
+
+use core::fmt::{self, Write};
+
+use heapless::String;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name used to detect escaped strings in `deserialize_newtype_struct`
+pub(crate) const ESCAPED_STR_NAME: &str = "$serde_at::EscapedStr";
+
+/// Escape scheme of a quoted string parameter.
+///
+/// Escape sequences start with a backslash, which allows the deserializer to
+/// find the closing quote of the string regardless of the scheme.
+pub trait EscapeScheme {
+    /// Writes `c` to `out`, escaped if needed
+    fn escape(&self, c: char, out: &mut dyn Write) -> fmt::Result;
+
+    /// Decodes the escape sequence following a backslash at the start of
+    /// `s`, returning the character and the number of bytes consumed
+    fn unescape(&self, s: &str) -> Option<(char, usize)>;
+}
+
+/// V.250 string escapes, where quotes, backslashes and control characters are
+/// written as a backslash and two hexadecimal digits, eg. `\22` for `"`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HexEscape;
+
+impl EscapeScheme for HexEscape {
+    fn escape(&self, c: char, out: &mut dyn Write) -> fmt::Result {
+        match c {
+            '"' | '\\' | '\x00'..='\x1f' | '\x7f' => write!(out, "\\{:02X}", c as u8),
+            c => out.write_char(c),
+        }
+    }
+
+    fn unescape(&self, s: &str) -> Option<(char, usize)> {
+        let digits = s.get(..2)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let c = u8::from_str_radix(digits, 16).ok()?;
+        Some((c as char, 2))
+    }
+}
+
+/// C style backslash escapes, eg. `\"`, `\\`, `\r` and `\n`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BackslashEscape;
+
+impl EscapeScheme for BackslashEscape {
+    fn escape(&self, c: char, out: &mut dyn Write) -> fmt::Result {
+        match c {
+            '"' => out.write_str("\\\""),
+            '\\' => out.write_str("\\\\"),
+            '\r' => out.write_str("\\r"),
+            '\n' => out.write_str("\\n"),
+            c => out.write_char(c),
+        }
+    }
+
+    fn unescape(&self, s: &str) -> Option<(char, usize)> {
+        let c = match s.as_bytes().first()? {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'r' => '\r',
+            b'n' => '\n',
+            _ => return None,
+        };
+        Some((c, 1))
+    }
+}
+
+/// String parameter escaped using the scheme `E`.
+///
+/// Serializes any string, eg. `&str` or `heapless::String`, and deserializes
+/// into a `heapless::String`. Used by `#[derive(AtatCmd)]` and
+/// `#[derive(AtatResp)]` for fields with `#[at_arg(escape = ..)]`.
+///
+/// Example:
+/// ```
+/// use heapless::String;
+/// use serde_at::{from_str, to_string, Escaped, HexEscape, SerializeOptions};
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Credentials {
+///     ssid: Escaped<String<32>, HexEscape>,
+/// }
+///
+/// let credentials = Credentials {
+///     ssid: Escaped(String::from("My \"Home\""), HexEscape),
+/// };
+/// let s: String<64> = to_string(
+///     &credentials,
+///     String::<32>::from("+SSID"),
+///     SerializeOptions::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, String::<64>::from("AT+SSID=\"My \\22Home\\22\"\r\n"));
+///
+/// let incoming: Credentials = from_str("+SSID: \"My \\22Home\\22\"").unwrap();
+/// assert_eq!(incoming, credentials);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Escaped<T, E>(pub T, pub E);
+
+impl<T, E> Serialize for Escaped<T, E>
+where
+    T: AsRef<str>,
+    E: EscapeScheme,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<T, E> fmt::Display for Escaped<T, E>
+where
+    T: AsRef<str>,
+    E: EscapeScheme,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.as_ref().chars() {
+            self.1.escape(c, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de, E, const N: usize> Deserialize<'de> for Escaped<String<N>, E>
+where
+    E: EscapeScheme + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EscapedVisitor<E, const N: usize>(E);
+
+        impl<'de, E, const N: usize> de::Visitor<'de> for EscapedVisitor<E, N>
+        where
+            E: EscapeScheme,
+        {
+            type Value = String<N>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an escaped string")
+            }
+
+            fn visit_str<Er>(self, v: &str) -> Result<Self::Value, Er>
+            where
+                Er: de::Error,
+            {
+                let mut s = String::new();
+                let mut rest = v;
+                while let Some(i) = rest.find('\\') {
+                    s.push_str(&rest[..i])
+                        .map_err(|_| Er::invalid_length(v.len(), &self))?;
+                    let (c, len) = self
+                        .0
+                        .unescape(&rest[i + 1..])
+                        .ok_or_else(|| Er::invalid_value(de::Unexpected::Str(v), &self))?;
+                    s.push(c).map_err(|_| Er::invalid_length(v.len(), &self))?;
+                    rest = &rest[i + 1 + len..];
+                }
+                s.push_str(rest)
+                    .map_err(|_| Er::invalid_length(v.len(), &self))?;
+                Ok(s)
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_str(self)
+            }
+        }
+
+        let s = deserializer
+            .deserialize_newtype_struct(ESCAPED_STR_NAME, EscapedVisitor(E::default()))?;
+        Ok(Self(s, E::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_str, to_string, SerializeOptions};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Publish {
+        topic: Escaped<String<16>, BackslashEscape>,
+        payload: Escaped<String<32>, HexEscape>,
+        qos: u8,
+    }
+
+    #[test]
+    fn round_trip() {
+        let publish = Publish {
+            topic: Escaped(String::from("a\"b\\c"), BackslashEscape),
+            payload: Escaped(String::from("{\"on\":1,\"x\":\"\\\"}\r\n"), HexEscape),
+            qos: 1,
+        };
+
+        let s: String<128> = to_string(
+            &publish,
+            String::<16>::from("+PUB"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            s,
+            String::<128>::from(
+                "AT+PUB=\"a\\\"b\\\\c\",\"{\\22on\\22:1,\\22x\\22:\\22\\5C\\22}\\0D\\0A\",1\r\n"
+            )
+        );
+
+        let incoming: Publish =
+            from_str("+PUB: \"a\\\"b\\\\c\",\"{\\22on\\22:1,\\22x\\22:\\22\\5C\\22}\\0D\\0A\",1")
+                .unwrap();
+        assert_eq!(incoming, publish);
+    }
+
+    #[test]
+    fn invalid() {
+        // Unknown escape sequence
+        assert!(from_str::<Publish>("+PUB: \"a\\x\",\"\",1").is_err());
+        // Unterminated string
+        assert!(from_str::<Publish>("+PUB: \"a\\\",\"\",1").is_err());
+        // Too long after unescaping
+        assert!(
+            from_str::<Publish>("+PUB: \"\",\"0123456789012345678901234567890123\",1").is_err()
+        );
+    }
+}
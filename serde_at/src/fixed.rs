@@ -0,0 +1,117 @@
+//! Floats formatted with a fixed number of decimals, eg. GNSS coordinates
+
+use core::fmt::Write;
+
+use heapless::String;
+use serde::{ser, Serialize, Serializer};
+
+/// Floats that can be serialized with a fixed number of decimals
+pub trait FixedFloat {
+    /// Maximum number of decimals
+    const MAX_PRECISION: usize;
+    /// Length of the largest value with `MAX_PRECISION` decimals, as its
+    /// sign, integer digits, decimal point and decimals
+    const MAX_LEN: usize;
+
+    fn serialize_fixed<S>(&self, precision: usize, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+// The maximum precision matches the decimals of the `AtatLen` of the float, so
+// any value fits in `MAX_LEN`
+macro_rules! impl_fixed_float {
+    ($fxx:ident, $digits:expr, $precision:expr) => {
+        impl FixedFloat for $fxx {
+            const MAX_PRECISION: usize = $precision;
+            const MAX_LEN: usize = $digits + $precision + 2;
+
+            fn serialize_fixed<S>(&self, precision: usize, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                if precision > Self::MAX_PRECISION {
+                    return Err(ser::Error::custom("precision too large"));
+                }
+                let mut s: String<{ $digits + $precision + 2 }> = String::new();
+                write!(s, "{:.*}", precision, self)
+                    .map_err(|_| ser::Error::custom("float does not fit"))?;
+                serializer.serialize_bytes(s.as_bytes())
+            }
+        }
+    };
+}
+
+impl_fixed_float!(f32, 39, 7);
+impl_fixed_float!(f64, 309, 16);
+
+impl<T> FixedFloat for Option<T>
+where
+    T: FixedFloat,
+{
+    const MAX_PRECISION: usize = T::MAX_PRECISION;
+    const MAX_LEN: usize = T::MAX_LEN;
+
+    fn serialize_fixed<S>(&self, precision: usize, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(value) => value.serialize_fixed(precision, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Serialized length of any `T` with `precision` decimals. Used by
+/// `#[derive(AtatCmd)]` for fields with `#[at_arg(precision = ..)]`, failing to
+/// compile if `precision` is above `T::MAX_PRECISION`.
+pub const fn fixed_len<T: FixedFloat>(precision: usize) -> usize {
+    assert!(
+        precision <= T::MAX_PRECISION,
+        "precision is above the maximum precision of the float type"
+    );
+    T::MAX_LEN - T::MAX_PRECISION + precision
+}
+
+/// Serializes a borrowed float with a fixed number of decimals, eg.
+/// `Fixed(&55.5, 3)` as `55.500`. Used by `#[derive(AtatCmd)]` for fields with
+/// `#[at_arg(precision = ..)]`. Fails for a precision above
+/// `FixedFloat::MAX_PRECISION`.
+///
+/// Example:
+/// ```
+/// use heapless::String;
+/// use serde_at::{to_string, Fixed, SerializeOptions};
+/// use serde_derive::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct SetPosition<'a> {
+///     lat: Fixed<'a, f64>,
+///     lon: Fixed<'a, f64>,
+/// }
+///
+/// let s: String<64> = to_string(
+///     &SetPosition {
+///         lat: Fixed(&55.67, 6),
+///         lon: Fixed(&-12.5, 6),
+///     },
+///     String::<32>::from("+POS"),
+///     SerializeOptions::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, String::<64>::from("AT+POS=55.670000,-12.500000\r\n"));
+/// ```
+pub struct Fixed<'a, T>(pub &'a T, pub usize);
+
+impl<'a, T> Serialize for Fixed<'a, T>
+where
+    T: FixedFloat,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_fixed(self.1, serializer)
+    }
+}
@@ -8,14 +8,29 @@
 #![cfg_attr(all(not(test), not(feature = "std")), no_std)]
 
 pub mod de;
+pub mod encoding;
+pub mod escape;
+pub mod fixed;
+pub mod radix;
 pub mod ser;
 
 pub use serde;
 
 #[doc(inline)]
-pub use self::de::{from_slice, from_str, CharVec};
+pub use self::de::{
+    from_slice, from_slice_with_context, from_slice_with_options, from_str, CharVec,
+    DeserializeOptions, Lines, TrailingParams, ValueRange,
+};
 #[doc(inline)]
-pub use self::ser::{to_string, to_vec, Bytes, SerializeOptions};
+pub use self::encoding::{Base64Bytes, HexBytes};
+#[doc(inline)]
+pub use self::escape::{BackslashEscape, EscapeScheme, Escaped, HexEscape};
+#[doc(inline)]
+pub use self::fixed::{fixed_len, Fixed, FixedFloat};
+#[doc(inline)]
+pub use self::radix::{Formatted, HexStr, IntFormat, RadixInt, RadixSeed};
+#[doc(inline)]
+pub use self::ser::{to_slice, to_string, to_vec, to_writer, Bytes, SerializeOptions};
 
 unsafe fn uninitialized<T>() -> T {
     core::mem::MaybeUninit::uninit().assume_init()
@@ -0,0 +1,500 @@
+//! Integers formatted in a radix other than 10, eg. hexadecimal cell
+//! identifiers (`"1A2B"`) or bitmasks
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name used to detect radix integers in `deserialize_newtype_struct`
+pub(crate) const RADIX_INT_NAME: &str = "$serde_at::RadixInt";
+
+/// Digits of the longest integer, a `u128` in binary, plus the quotes
+const MAX_LEN: usize = 130;
+
+/// Formatting of an integer in a radix other than 10
+///
+/// When deserializing, only the radix is used. Digits are accepted in either
+/// case, with or without quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntFormat {
+    radix: u32,
+    width: usize,
+    upper_case: bool,
+    quoted: bool,
+}
+
+impl IntFormat {
+    /// Format in `radix`, which must be in the range `2..=36`
+    #[must_use]
+    pub const fn new(radix: u32) -> Self {
+        Self {
+            radix,
+            width: 0,
+            upper_case: false,
+            quoted: false,
+        }
+    }
+
+    /// Hexadecimal format, eg. `1a2b`
+    #[must_use]
+    pub const fn hex() -> Self {
+        Self::new(16)
+    }
+
+    /// Binary format, eg. `1011`
+    #[must_use]
+    pub const fn bin() -> Self {
+        Self::new(2)
+    }
+
+    /// Pad the digits with leading zeros, up to `width` digits
+    #[must_use]
+    pub const fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Use upper case letters for digits above 9, eg. `1A2B`
+    #[must_use]
+    pub const fn upper_case(mut self, upper_case: bool) -> Self {
+        self.upper_case = upper_case;
+        self
+    }
+
+    /// Enclose the digits in quotes, eg. `"1A2B"`
+    #[must_use]
+    pub const fn quoted(mut self, quoted: bool) -> Self {
+        self.quoted = quoted;
+        self
+    }
+
+    /// Writes the formatted `value` to the end of `buf`, returning the index
+    /// of the first byte written
+    fn write(self, mut value: u128, buf: &mut [u8; MAX_LEN]) -> Option<usize> {
+        if !(2..=36).contains(&self.radix) || self.width > MAX_LEN - 2 {
+            return None;
+        }
+
+        let mut i = MAX_LEN;
+        if self.quoted {
+            i -= 1;
+            buf[i] = b'"';
+        }
+        let end = i;
+        while value > 0 || i == end || end - i < self.width {
+            let digit =
+                core::char::from_digit((value % u128::from(self.radix)) as u32, self.radix)?;
+            i -= 1;
+            buf[i] = if self.upper_case {
+                digit.to_ascii_uppercase() as u8
+            } else {
+                digit as u8
+            };
+            value /= u128::from(self.radix);
+        }
+        if self.quoted {
+            i -= 1;
+            buf[i] = b'"';
+        }
+        Some(i)
+    }
+}
+
+/// Integers that can be serialized and deserialized in an [`IntFormat`]
+pub trait RadixInt: Sized {
+    /// Number of bits of the integer
+    const BITS: u32;
+
+    fn serialize_radix<S>(&self, format: IntFormat, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    fn deserialize_radix<'de, D>(format: IntFormat, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+struct RadixVisitor<T>(IntFormat, PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for RadixVisitor<T>
+where
+    T: TryFrom<u128>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "an integer in radix {}", self.0.radix)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if !(2..=36).contains(&self.0.radix) {
+            return Err(E::custom("invalid radix"));
+        }
+        u128::from_str_radix(v, self.0.radix)
+            .ok()
+            .and_then(|v| T::try_from(v).ok())
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+macro_rules! impl_radix_int {
+    ($($uxx:ident),*) => {
+        $(
+            impl RadixInt for $uxx {
+                const BITS: u32 = $uxx::BITS;
+
+                fn serialize_radix<S>(&self, format: IntFormat, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: Serializer,
+                {
+                    let mut buf = [0; MAX_LEN];
+                    let start = format
+                        .write(*self as u128, &mut buf)
+                        .ok_or_else(|| ser::Error::custom("invalid integer format"))?;
+                    serializer.serialize_bytes(&buf[start..])
+                }
+
+                fn deserialize_radix<'de, D>(format: IntFormat, deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    deserializer.deserialize_newtype_struct(
+                        RADIX_INT_NAME,
+                        RadixVisitor(format, PhantomData),
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_int!(u8, u16, u32, u64, u128, usize);
+
+impl<T> RadixInt for Option<T>
+where
+    T: RadixInt,
+{
+    const BITS: u32 = T::BITS;
+
+    fn serialize_radix<S>(&self, format: IntFormat, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(value) => value.serialize_radix(format, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_radix<'de, D>(format: IntFormat, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OptionVisitor<T>(IntFormat, PhantomData<T>);
+
+        impl<'de, T> de::Visitor<'de> for OptionVisitor<T>
+        where
+            T: RadixInt,
+        {
+            type Value = Option<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an optional integer")
+            }
+
+            fn visit_none<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(None)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                T::deserialize_radix(self.0, deserializer).map(Some)
+            }
+        }
+
+        deserializer.deserialize_option(OptionVisitor(format, PhantomData))
+    }
+}
+
+/// Serializes a borrowed integer in the given format. Used by
+/// `#[derive(AtatCmd)]` for fields with `#[at_arg(format = ..)]`.
+pub struct Formatted<'a, T>(pub &'a T, pub IntFormat);
+
+impl<'a, T> Serialize for Formatted<'a, T>
+where
+    T: RadixInt,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_radix(self.1, serializer)
+    }
+}
+
+/// Deserializes an integer in the given format. Used by `#[derive(AtatResp)]`
+/// for fields with `#[at_arg(format = ..)]`.
+pub struct RadixSeed<T>(IntFormat, PhantomData<T>);
+
+impl<T> RadixSeed<T> {
+    #[must_use]
+    pub fn new(format: IntFormat) -> Self {
+        Self(format, PhantomData)
+    }
+}
+
+impl<'de, T> de::DeserializeSeed<'de> for RadixSeed<T>
+where
+    T: RadixInt,
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_radix(self.0, deserializer)
+    }
+}
+
+/// Wrapper type for integers formatted as hexadecimal strings, eg. the cell
+/// identifiers of `+CREG: 2,1,"1A2B","00C3D4E5",7`
+///
+/// Example:
+/// ```
+/// use heapless::String;
+/// use serde_at::{from_str, to_string, HexStr, SerializeOptions};
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Registration {
+///     stat: u8,
+///     lac: HexStr<u16>,
+/// }
+///
+/// let incoming: Registration = from_str("+CREG: 1,\"1A2B\"").unwrap();
+/// assert_eq!(incoming.lac.val, 0x1a2b);
+///
+/// let lac = HexStr {
+///     val: 0xc3,
+///     width: 4,
+///     upper_case: true,
+///     quoted: true,
+/// };
+/// let s: String<32> = to_string(
+///     &Registration { stat: 1, lac },
+///     String::<32>::from("+CREG"),
+///     SerializeOptions::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, String::<32>::from("AT+CREG=1,\"00C3\"\r\n"));
+/// ```
+///
+/// Only `val` is deserialized. `width`, `upper_case` and `quoted` are left at
+/// their defaults, so a deserialized `HexStr` serializes as plain lower case
+/// digits, whatever the received formatting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct HexStr<T> {
+    pub val: T,
+    /// Minimum number of digits, padded with leading zeros. Serializing fails
+    /// for a `width` above the number of digits of the largest `T`.
+    pub width: usize,
+    pub upper_case: bool,
+    pub quoted: bool,
+}
+
+impl<T> HexStr<T> {
+    fn format(&self) -> IntFormat {
+        IntFormat::hex()
+            .width(self.width)
+            .upper_case(self.upper_case)
+            .quoted(self.quoted)
+    }
+}
+
+impl<T> Serialize for HexStr<T>
+where
+    T: RadixInt,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.width > (T::BITS as usize).div_ceil(4) {
+            return Err(ser::Error::custom("width above the digits of the integer"));
+        }
+        self.val.serialize_radix(self.format(), serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for HexStr<T>
+where
+    T: RadixInt,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Self {
+            val: T::deserialize_radix(IntFormat::hex(), deserializer)?,
+            width: 0,
+            upper_case: false,
+            quoted: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_str, to_string, SerializeOptions};
+    use heapless::String;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Serialize)]
+    struct Formats<'a> {
+        hex: Formatted<'a, u32>,
+        bin: Formatted<'a, u8>,
+        base36: Formatted<'a, u64>,
+        none: Formatted<'a, Option<u16>>,
+        last: u8,
+    }
+
+    #[test]
+    fn serialize() {
+        let s: String<64> = to_string(
+            &Formats {
+                hex: Formatted(&0xbeef, IntFormat::hex().width(8).upper_case(true)),
+                bin: Formatted(&5, IntFormat::bin().width(4).quoted(true)),
+                base36: Formatted(&35, IntFormat::new(36)),
+                none: Formatted(&None, IntFormat::hex()),
+                last: 0,
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<64>::from("AT+CMD=0000BEEF,\"0101\",z,,0\r\n"));
+
+        let s: String<160> = to_string(
+            &HexStr {
+                val: 0_u8,
+                ..HexStr::default()
+            },
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<160>::from("0"));
+
+        // `width` is bounded by the digits of the largest value
+        let s: String<160> = to_string(
+            &HexStr {
+                val: 0xc3_u16,
+                width: 4,
+                ..HexStr::default()
+            },
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<160>::from("00c3"));
+        let res: crate::ser::Result<String<160>> = to_string(
+            &HexStr {
+                val: 0xc3_u16,
+                width: 5,
+                ..HexStr::default()
+            },
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        );
+        assert!(res.is_err());
+
+        let res: crate::ser::Result<String<160>> = to_string(
+            &Formatted(&1_u8, IntFormat::new(37)),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        );
+        assert!(res.is_err());
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Registration {
+        stat: u8,
+        lac: HexStr<u16>,
+        ci: Option<HexStr<u32>>,
+        mask: HexStr<u8>,
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(
+            from_str("+CREG: 1,\"1a2B\",00C3D4E5,ff"),
+            Ok(Registration {
+                stat: 1,
+                lac: HexStr {
+                    val: 0x1a2b,
+                    ..HexStr::default()
+                },
+                ci: Some(HexStr {
+                    val: 0x00c3_d4e5,
+                    ..HexStr::default()
+                }),
+                mask: HexStr {
+                    val: 0xff,
+                    ..HexStr::default()
+                },
+            })
+        );
+        assert_eq!(
+            from_str("+CREG: 1,\"1A2B\",,0"),
+            Ok(Registration {
+                stat: 1,
+                lac: HexStr {
+                    val: 0x1a2b,
+                    ..HexStr::default()
+                },
+                ci: None,
+                mask: HexStr::default(),
+            })
+        );
+
+        // Out of range, and not a hexadecimal digit
+        assert!(from_str::<Registration>("+CREG: 1,\"1A2B\",,100").is_err());
+        assert!(from_str::<Registration>("+CREG: 1,\"1G2B\",,0").is_err());
+        assert!(from_str::<Registration>("+CREG: 1,\"1A2B,,0").is_err());
+    }
+
+    #[test]
+    fn deserialize_resets_format() {
+        let lac: HexStr<u16> = from_str("\"00C3\"").unwrap();
+        assert_eq!(
+            lac,
+            HexStr {
+                val: 0xc3,
+                ..HexStr::default()
+            }
+        );
+
+        let s: String<32> =
+            to_string(&lac, String::<32>::from(""), SerializeOptions::default()).unwrap();
+        assert_eq!(s, String::<32>::from("c3"));
+    }
+}
@@ -1,33 +1,18 @@
 use serde::ser;
 
-use heapless::ArrayLength;
+use crate::ser::{Error, Result, Serializer, Write};
 
-use crate::ser::{Error, Result, Serializer};
-
-pub struct SerializeTupleVariant<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
-    ser: &'a mut Serializer<'b, B, C>,
-    first: bool,
+pub struct SerializeTupleVariant<'a, 'b, W: Write + ?Sized> {
+    ser: &'a mut Serializer<'b, W>,
 }
 
-impl<'a, 'b, B, C> SerializeTupleVariant<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
-    pub(crate) fn new(ser: &'a mut Serializer<'b, B, C>) -> Self {
-        SerializeTupleVariant { ser, first: true }
+impl<'a, 'b, W: Write + ?Sized> SerializeTupleVariant<'a, 'b, W> {
+    pub(crate) fn new(ser: &'a mut Serializer<'b, W>) -> Self {
+        SerializeTupleVariant { ser }
     }
 }
 
-impl<'a, 'b, B, C> ser::SerializeTupleVariant for SerializeTupleVariant<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
+impl<'a, 'b, W: Write + ?Sized> ser::SerializeTupleVariant for SerializeTupleVariant<'a, 'b, W> {
     type Ok = ();
     type Error = Error;
 
@@ -35,13 +20,7 @@ where
     where
         T: ser::Serialize,
     {
-        if !self.first {
-            self.ser.buf.push(b',')?;
-        }
-        self.first = false;
-
-        value.serialize(&mut *self.ser)?;
-        Ok(())
+        self.ser.serialize_param(Some(b','), value)
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -49,30 +28,17 @@ where
     }
 }
 
-pub struct SerializeStructVariant<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
-    ser: &'a mut Serializer<'b, B, C>,
-    first: bool,
+pub struct SerializeStructVariant<'a, 'b, W: Write + ?Sized> {
+    ser: &'a mut Serializer<'b, W>,
 }
 
-impl<'a, 'b, B, C> SerializeStructVariant<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
-    pub(crate) fn new(ser: &'a mut Serializer<'b, B, C>) -> Self {
-        SerializeStructVariant { ser, first: true }
+impl<'a, 'b, W: Write + ?Sized> SerializeStructVariant<'a, 'b, W> {
+    pub(crate) fn new(ser: &'a mut Serializer<'b, W>) -> Self {
+        SerializeStructVariant { ser }
     }
 }
 
-impl<'a, 'b, B, C> ser::SerializeStructVariant for SerializeStructVariant<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
+impl<'a, 'b, W: Write + ?Sized> ser::SerializeStructVariant for SerializeStructVariant<'a, 'b, W> {
     type Ok = ();
     type Error = Error;
 
@@ -80,13 +46,7 @@ where
     where
         T: ser::Serialize,
     {
-        if !self.first {
-            self.ser.buf.push(b',')?;
-        }
-        self.first = false;
-
-        value.serialize(&mut *self.ser)?;
-        Ok(())
+        self.ser.serialize_param(Some(b','), value)
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -1,21 +1,18 @@
 //! Serialize a Rust data structure into AT Command strings
 
-use core::fmt::{self, Write};
+use core::fmt::{self, Write as _};
 
 use serde::ser;
 
 use crate::de::CharVec;
 
-use heapless::{
-    consts::{U16, U32},
-    String, Vec,
-};
+use heapless::{String, Vec};
 
 mod enum_;
 mod struct_;
 
 use self::enum_::{SerializeStructVariant, SerializeTupleVariant};
-use self::struct_::SerializeStruct;
+use self::struct_::{SerializeStruct, SerializeTuple};
 
 /// Serialization result
 pub type Result<T> = ::core::result::Result<T, Error>;
@@ -25,7 +22,7 @@ pub type Result<T> = ::core::result::Result<T, Error>;
 ///
 /// Example:
 /// ```
-/// use heapless::{consts, String};
+/// use heapless::String;
 /// use serde_at::{to_string, Bytes, SerializeOptions};
 /// use serde_derive::Serialize;
 ///
@@ -38,14 +35,14 @@ pub type Result<T> = ::core::result::Result<T, Error>;
 /// let b = WithBytes {
 ///     s: Bytes(&slice[..]),
 /// };
-/// let s: String<consts::U32> = to_string(
+/// let s: String<32> = to_string(
 ///     &b,
-///     String::<consts::U32>::from("+CMD"),
+///     String::<32>::from("+CMD"),
 ///     SerializeOptions::default(),
 /// )
 /// .unwrap();
 ///
-/// assert_eq!(s, String::<consts::U32>::from("AT+CMD=Some bytes\r\n"));
+/// assert_eq!(s, String::<32>::from("AT+CMD=Some bytes\r\n"));
 /// ```
 #[derive(Clone, PartialEq)]
 pub struct Bytes<'a>(pub &'a [u8]);
@@ -59,17 +56,14 @@ impl<'a> serde::Serialize for Bytes<'a> {
     }
 }
 
-impl<T> serde::Serialize for CharVec<T>
-where
-    T: heapless::ArrayLength<char> + heapless::ArrayLength<u8>,
-{
+impl<const N: usize> serde::Serialize for CharVec<N> {
     fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         serde::Serializer::serialize_bytes(
             serializer,
-            &self.0.iter().map(|c| *c as u8).collect::<Vec<u8, T>>()[0..self.0.len()],
+            &self.0.iter().map(|c| *c as u8).collect::<Vec<u8, N>>()[0..self.0.len()],
         )
     }
 }
@@ -108,6 +102,12 @@ impl<'a> Default for SerializeOptions<'a> {
 pub enum Error {
     /// Buffer is full
     BufferFull,
+    /// The value cannot be represented in an AT Command string
+    Unsupported,
+    /// Error with a custom message that we had to discard
+    CustomError,
+    /// The writer failed to write the bytes, eg. on a serial port error
+    Write,
 }
 
 impl From<()> for Error {
@@ -124,35 +124,130 @@ impl From<u8> for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Buffer is full")
+        match self {
+            Self::BufferFull => write!(f, "Buffer is full"),
+            Self::Unsupported => write!(f, "Unsupported value"),
+            Self::CustomError => write!(f, "Custom error"),
+            Self::Write => write!(f, "Write error"),
+        }
     }
 }
 
 #[cfg(any(test, feature = "std"))]
 impl std::error::Error for Error {}
 
-pub(crate) struct Serializer<'a, B, C>
-where
-    B: heapless::ArrayLength<u8>,
-    C: heapless::ArrayLength<u8>,
-{
-    buf: Vec<u8, B>,
-    cmd: String<C>,
+/// Destination of the serialized bytes, allowing to serialize a command
+/// straight into eg. a serial port, without materialising it in a buffer.
+///
+/// Implemented for `heapless::Vec<u8, N>`, and for `&mut [u8]`, which is
+/// advanced past the written bytes.
+pub trait Write {
+    /// Writes all of `bytes`, or fails with eg. `Error::BufferFull`
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()>;
+}
+
+impl<const N: usize> Write for Vec<u8, N> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.extend_from_slice(bytes)?;
+        Ok(())
+    }
+}
+
+impl Write for &mut [u8] {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        if bytes.len() > self.len() {
+            return Err(Error::BufferFull);
+        }
+        let (head, tail) = core::mem::take(self).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        *self = tail;
+        Ok(())
+    }
+}
+
+pub(crate) struct Serializer<'a, W: ?Sized> {
+    writer: &'a mut W,
+    /// Number of bytes written to `writer`
+    written: usize,
+    /// Separators of empty parameters, which are only written once a
+    /// non-empty parameter follows. All but the first are `,`.
+    pending: usize,
+    pending_sep: u8,
+    cmd: &'a str,
     options: SerializeOptions<'a>,
 }
 
-impl<'a, B, C> Serializer<'a, B, C>
-where
-    B: heapless::ArrayLength<u8>,
-    C: heapless::ArrayLength<u8>,
-{
-    fn new(cmd: String<C>, options: SerializeOptions<'a>) -> Self {
+impl<'a, W: Write + ?Sized> Serializer<'a, W> {
+    fn new(writer: &'a mut W, cmd: &'a str, options: SerializeOptions<'a>) -> Self {
         Serializer {
-            buf: Vec::new(),
+            writer,
+            written: 0,
+            pending: 0,
+            pending_sep: b',',
             cmd,
             options,
         }
     }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.flush_pending()?;
+        self.writer.write_bytes(bytes)?;
+        self.written += bytes.len();
+        Ok(())
+    }
+
+    fn push_sep(&mut self, sep: u8) -> Result<()> {
+        if self.pending > 0 && sep != b',' {
+            self.flush_pending()?;
+        }
+        if self.pending == 0 {
+            self.pending_sep = sep;
+        }
+        self.pending += 1;
+        Ok(())
+    }
+
+    fn flush_pending(&mut self) -> Result<()> {
+        if self.pending > 0 {
+            self.writer.write_bytes(&[self.pending_sep])?;
+            for _ in 1..self.pending {
+                self.writer.write_bytes(b",")?;
+            }
+            self.written += self.pending;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Serializes a single parameter, preceded by the separator `sep`.
+    ///
+    /// Empty values (eg. `None`) leave an empty slot between the separators,
+    /// eg. `1,,3`, as the separators are only written once a non-empty value
+    /// follows, including values of the enclosing tuples and variants.
+    fn serialize_param<T: ?Sized>(&mut self, sep: Option<u8>, value: &T) -> Result<()>
+    where
+        T: ser::Serialize,
+    {
+        if let Some(sep) = sep {
+            self.push_sep(sep)?;
+        }
+        value.serialize(&mut *self)
+    }
+
+    /// Drops the separators of trailing empty parameters at the end of the
+    /// command, which are all still pending
+    fn trim_empty(&mut self) {
+        self.pending = 0;
+    }
+}
+
+impl<'a, W: Write + ?Sized> fmt::Write for Serializer<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes()).map_err(|_| fmt::Error)
+    }
 }
 
 // NOTE(serialize_*signed) This is basically the numtoa implementation minus the lookup tables,
@@ -173,7 +268,7 @@ macro_rules! serialize_unsigned {
             i -= 1;
         }
 
-        $self.buf.extend_from_slice(&buf[i..])?;
+        $self.write_bytes(&buf[i..])?;
         Ok(())
     }};
 }
@@ -207,40 +302,27 @@ macro_rules! serialize_signed {
         } else {
             i += 1;
         }
-        $self.buf.extend_from_slice(&buf[i..])?;
+        $self.write_bytes(&buf[i..])?;
         Ok(())
     }};
 }
 
-macro_rules! serialize_fmt {
-    ($self:ident, $uxx:ident, $fmt:expr, $v:expr) => {{
-        let mut s: String<$uxx> = String::new();
-        write!(&mut s, $fmt, $v).unwrap();
-        $self.buf.extend_from_slice(s.as_bytes())?;
-        Ok(())
-    }};
-}
-
-impl<'a, 'b, B, C> ser::Serializer for &'a mut Serializer<'b, B, C>
-where
-    B: heapless::ArrayLength<u8>,
-    C: heapless::ArrayLength<u8>,
-{
+impl<'a, 'b, W: Write + ?Sized> ser::Serializer for &'a mut Serializer<'b, W> {
     type Ok = ();
     type Error = Error;
     type SerializeSeq = Unreachable;
-    type SerializeTuple = Unreachable;
-    type SerializeTupleStruct = Unreachable;
-    type SerializeTupleVariant = SerializeTupleVariant<'a, 'b, B, C>;
+    type SerializeTuple = SerializeTuple<'a, 'b, W>;
+    type SerializeTupleStruct = SerializeStruct<'a, 'b, W>;
+    type SerializeTupleVariant = SerializeTupleVariant<'a, 'b, W>;
     type SerializeMap = Unreachable;
-    type SerializeStruct = SerializeStruct<'a, 'b, B, C>;
-    type SerializeStructVariant = SerializeStructVariant<'a, 'b, B, C>;
+    type SerializeStruct = SerializeStruct<'a, 'b, W>;
+    type SerializeStructVariant = SerializeStructVariant<'a, 'b, W>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
         if v {
-            self.buf.extend_from_slice(b"true")?;
+            self.write_bytes(b"true")?;
         } else {
-            self.buf.extend_from_slice(b"false")?;
+            self.write_bytes(b"false")?;
         }
 
         Ok(())
@@ -286,35 +368,34 @@ where
         serialize_unsigned!(self, 20, v)
     }
 
+    /// Floats are written in decimal notation, as modems don't accept
+    /// exponents. Use `Fixed` for a fixed number of decimals.
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        serialize_fmt!(self, U16, "{:e}", v)
+        write!(self, "{}", v).map_err(|_| Error::BufferFull)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        serialize_fmt!(self, U32, "{:e}", v)
+        write!(self, "{}", v).map_err(|_| Error::BufferFull)
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok> {
-        self.buf.push(b'"')?;
-        self.buf.push(v as u8)?;
-        self.buf.push(b'"')?;
+        self.write_bytes(&[b'"', v as u8, b'"'])?;
         Ok(())
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        self.buf.push(b'"')?;
-        self.buf.extend_from_slice(v.as_bytes())?;
-        self.buf.push(b'"')?;
+        self.write_bytes(b"\"")?;
+        self.write_bytes(v.as_bytes())?;
+        self.write_bytes(b"\"")?;
         Ok(())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        self.buf.extend_from_slice(v)?;
+        self.write_bytes(v)?;
         Ok(())
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        self.buf.truncate(self.buf.len() - 1);
         Ok(())
     }
 
@@ -326,15 +407,13 @@ where
     }
 
     fn serialize_unit(self) -> Result<Self::Ok> {
-        unreachable!()
+        Ok(())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
-        self.buf
-            .extend_from_slice(self.options.cmd_prefix.as_bytes())?;
-        self.buf.extend_from_slice(self.cmd.as_bytes())?;
-        self.buf
-            .extend_from_slice(self.options.termination.as_bytes())?;
+        self.write_bytes(self.options.cmd_prefix.as_bytes())?;
+        self.write_bytes(self.cmd.as_bytes())?;
+        self.write_bytes(self.options.termination.as_bytes())?;
         Ok(())
     }
 
@@ -365,24 +444,23 @@ where
         T: ser::Serialize,
     {
         self.serialize_u32(variant_index)?;
-        self.buf.push(b',')?;
-        value.serialize(self)
+        self.serialize_param(Some(b','), value)
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        unreachable!()
+        Err(Error::Unsupported)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        unreachable!()
+        Ok(SerializeTuple::new(self))
     }
 
     fn serialize_tuple_struct(
         self,
-        _name: &'static str,
-        _len: usize,
+        name: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        unreachable!()
+        self.serialize_struct(name, len)
     }
 
     fn serialize_tuple_variant(
@@ -393,18 +471,16 @@ where
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
         self.serialize_u32(variant_index)?;
-        self.buf.push(b',')?;
         Ok(SerializeTupleVariant::new(self))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        unreachable!()
+        Err(Error::Unsupported)
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        self.buf
-            .extend_from_slice(self.options.cmd_prefix.as_bytes())?;
-        self.buf.extend_from_slice(self.cmd.as_bytes())?;
+        self.write_bytes(self.options.cmd_prefix.as_bytes())?;
+        self.write_bytes(self.cmd.as_bytes())?;
         Ok(SerializeStruct::new(self))
     }
 
@@ -416,69 +492,97 @@ where
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
         self.serialize_u32(variant_index)?;
-        self.buf.push(b',')?;
         Ok(SerializeStructVariant::new(self))
     }
 
-    fn collect_str<T: ?Sized>(self, _value: &T) -> Result<Self::Ok> {
-        unreachable!()
+    /// Serializes the `Display` output of `value` as a quoted string
+    fn collect_str<T: ?Sized>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: fmt::Display,
+    {
+        self.write_bytes(b"\"")?;
+        write!(self, "{}", value).map_err(|_| Error::BufferFull)?;
+        self.write_bytes(b"\"")?;
+        Ok(())
     }
 }
 
 /// Serializes the given data structure as a string
-pub fn to_string<B, C, T>(
+pub fn to_string<const B: usize, const C: usize, T>(
     value: &T,
     cmd: String<C>,
     options: SerializeOptions<'_>,
 ) -> Result<String<B>>
 where
-    B: heapless::ArrayLength<u8>,
-    C: heapless::ArrayLength<u8>,
     T: ser::Serialize + ?Sized,
 {
-    let mut ser = Serializer::new(cmd, options);
-    value.serialize(&mut ser)?;
-    Ok(unsafe { String::from_utf8_unchecked(ser.buf) })
+    let buf = to_vec(value, cmd, options)?;
+    let mut s = String::new();
+    // The serializer only writes valid UTF-8
+    unsafe { *s.as_mut_vec() = buf };
+    Ok(s)
 }
 
 /// Serializes the given data structure as a byte vector
-pub fn to_vec<B, C, T>(
+pub fn to_vec<const B: usize, const C: usize, T>(
     value: &T,
     cmd: String<C>,
     options: SerializeOptions<'_>,
 ) -> Result<Vec<u8, B>>
 where
-    B: heapless::ArrayLength<u8>,
-    C: heapless::ArrayLength<u8>,
     T: ser::Serialize + ?Sized,
 {
-    let mut ser = Serializer::new(cmd, options);
+    let mut buf = Vec::new();
+    to_writer(value, &cmd, &mut buf, options)?;
+    Ok(buf)
+}
+
+/// Serializes the given data structure into `buf`, returning the number of
+/// bytes written
+pub fn to_slice<T>(
+    value: &T,
+    cmd: &str,
+    buf: &mut [u8],
+    options: SerializeOptions<'_>,
+) -> Result<usize>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut writer = buf;
+    to_writer(value, cmd, &mut writer, options)
+}
+
+/// Serializes the given data structure straight into `writer`, returning the
+/// number of bytes written.
+///
+/// The output is not buffered, so a failing serialization may leave a partial
+/// command in `writer`.
+pub fn to_writer<W, T>(
+    value: &T,
+    cmd: &str,
+    writer: &mut W,
+    options: SerializeOptions<'_>,
+) -> Result<usize>
+where
+    W: Write + ?Sized,
+    T: ser::Serialize + ?Sized,
+{
+    let mut ser = Serializer::new(writer, cmd, options);
     value.serialize(&mut ser)?;
-    Ok(ser.buf)
+    Ok(ser.written)
 }
 
 impl ser::Error for Error {
     fn custom<T>(_msg: T) -> Self {
-        unreachable!()
+        Self::CustomError
     }
 }
 
+/// Serializer state for unsupported compound types, which can never be
+/// constructed
 #[allow(clippy::empty_enum)]
 pub(crate) enum Unreachable {}
 
-impl ser::SerializeTupleStruct for Unreachable {
-    type Ok = ();
-    type Error = Error;
-
-    fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
-        unreachable!()
-    }
-
-    fn end(self) -> Result<Self::Ok> {
-        unreachable!()
-    }
-}
-
 impl ser::SerializeMap for Unreachable {
     type Ok = ();
     type Error = Error;
@@ -487,18 +591,18 @@ impl ser::SerializeMap for Unreachable {
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        match *self {}
     }
 
     fn serialize_value<T: ?Sized>(&mut self, _value: &T) -> Result<()>
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        match *self {}
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unreachable!()
+        match self {}
     }
 }
 
@@ -507,31 +611,18 @@ impl ser::SerializeSeq for Unreachable {
     type Error = Error;
 
     fn serialize_element<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
-        unreachable!()
-    }
-
-    fn end(self) -> Result<Self::Ok> {
-        unreachable!()
-    }
-}
-
-impl ser::SerializeTuple for Unreachable {
-    type Ok = ();
-    type Error = Error;
-
-    fn serialize_element<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
-        unreachable!()
+        match *self {}
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unreachable!()
+        match self {}
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use heapless::{consts, String};
+    use heapless::String;
     use serde_derive::{Deserialize, Serialize};
 
     #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -541,14 +632,14 @@ mod tests {
         ProtocolType(bool),
         /// • 1: APN - <param_val> defines the APN text string, e.g. "apn.provider.com"; the
         /// maximum length is 99. The factory-programmed value is an empty string.
-        APN(String<consts::U128>),
+        APN(String<128>),
         /// • 2: username - <param_val> is the user name text string for the authentication
         /// phase. The factory-programmed value is an empty string.
-        Username(String<consts::U128>),
+        Username(String<128>),
         /// • 3: password - <param_val> is the password text string for the authentication phase.
         /// Note: the AT+UPSD read command with param_tag = 3 is not allowed and the read
         /// all command does not display it
-        Password(String<consts::U128>),
+        Password(String<128>),
 
         QoSDelay3G(u32),
         CurrentProfileMap(u8),
@@ -578,28 +669,316 @@ mod tests {
     #[derive(Clone, PartialEq, Serialize, Deserialize)]
     struct Handle(pub usize);
 
+    #[derive(Clone, PartialEq, Serialize)]
+    struct CmdWithLen {
+        x: u32,
+        y: u32,
+    }
+
     #[test]
     fn tuple_struct() {
-        let s: String<consts::U32> = to_string(
+        let s: String<32> = to_string(
             &PacketSwitchedParam::QoSDelay3G(15),
-            String::<consts::U32>::from(""),
+            String::<32>::from(""),
             SerializeOptions::default(),
         )
         .unwrap();
 
-        assert_eq!(s, String::<consts::U32>::from("4,15"));
+        assert_eq!(s, String::<32>::from("4,15"));
     }
 
     #[test]
     fn newtype_struct() {
-        let s: String<consts::U32> = to_string(
+        let s: String<32> = to_string(
             &Handle(15),
-            String::<consts::U32>::from(""),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(s, String::<32>::from("15"));
+    }
+
+    #[test]
+    fn tuples() {
+        #[derive(Clone, PartialEq, Serialize)]
+        struct Signal(u8, u8);
+
+        let s: String<32> = to_string(
+            &Signal(21, 99),
+            String::<32>::from("+CSQ"),
             SerializeOptions::default(),
         )
         .unwrap();
+        assert_eq!(s, String::<32>::from("AT+CSQ=21,99\r\n"));
 
-        assert_eq!(s, String::<consts::U32>::from("15"));
+        #[derive(Clone, PartialEq, Serialize)]
+        struct WithTuple {
+            id: u8,
+            pair: (i16, bool),
+            last: u8,
+        }
+
+        let s: String<32> = to_string(
+            &WithTuple {
+                id: 1,
+                pair: (-2, true),
+                last: 3,
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("AT+CMD=1,-2,true,3\r\n"));
+    }
+
+    #[test]
+    fn empty_params() {
+        #[derive(Clone, PartialEq, Serialize)]
+        struct WithOptions {
+            a: Option<u8>,
+            b: Option<u8>,
+            c: Option<u8>,
+            d: Option<u8>,
+        }
+
+        let cases = [
+            ((Some(1), None, Some(3), None), "AT+CMD=1,,3\r\n"),
+            ((None, Some(2), None, None), "AT+CMD=,2\r\n"),
+            ((Some(1), None, None, None), "AT+CMD=1\r\n"),
+            ((None, None, None, None), "AT+CMD\r\n"),
+            ((None, None, None, Some(4)), "AT+CMD=,,,4\r\n"),
+        ];
+        for ((a, b, c, d), expected) in cases.iter().cloned() {
+            let s: String<32> = to_string(
+                &WithOptions { a, b, c, d },
+                String::<32>::from("+CMD"),
+                SerializeOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(s, String::<32>::from(expected));
+        }
+
+        #[derive(Clone, PartialEq, Serialize)]
+        enum Param {
+            Pair(Option<u8>, Option<u8>),
+            Single(Option<u8>),
+        }
+
+        let s: String<32> = to_string(
+            &(Param::Pair(None, Some(2)), Param::Pair(Some(1), None)),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("0,,2,0,1"));
+
+        let s: String<32> = to_string(
+            &Param::Single(None),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("1"));
+
+        // Empty parameters at the end of a nested tuple or variant keep their
+        // slot if another parameter follows
+        #[derive(Clone, PartialEq, Serialize)]
+        struct WithTuple {
+            id: u8,
+            pair: (Option<u8>, Option<u8>),
+            last: u8,
+        }
+
+        let s: String<32> = to_string(
+            &WithTuple {
+                id: 1,
+                pair: (Some(2), None),
+                last: 3,
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("AT+CMD=1,2,,3\r\n"));
+
+        #[derive(Clone, PartialEq, Serialize)]
+        struct WithVariant {
+            p: Param,
+            last: u8,
+        }
+
+        let s: String<32> = to_string(
+            &WithVariant {
+                p: Param::Pair(Some(1), None),
+                last: 3,
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("AT+CMD=0,1,,3\r\n"));
+
+        // and are dropped at the end of the command
+        let s: String<32> = to_string(
+            &Param::Pair(Some(1), None),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("0,1"));
+
+        #[derive(Clone, PartialEq, Serialize)]
+        struct TrailingTuple {
+            id: u8,
+            pair: (Option<u8>, Option<u8>),
+        }
+
+        let s: String<32> = to_string(
+            &TrailingTuple {
+                id: 1,
+                pair: (None, None),
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<32>::from("AT+CMD=1\r\n"));
+    }
+
+    #[test]
+    fn floats() {
+        #[derive(Clone, PartialEq, Serialize)]
+        struct Floats {
+            a: f32,
+            b: f64,
+            c: f32,
+        }
+
+        let s: String<64> = to_string(
+            &Floats {
+                a: 1.5e10,
+                b: -0.000_125,
+                c: 3.0,
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<64>::from("AT+CMD=15000000000,-0.000125,3\r\n"));
+
+        #[derive(Serialize)]
+        struct FixedFloats<'a> {
+            lat: crate::Fixed<'a, f64>,
+            alt: crate::Fixed<'a, f32>,
+            acc: crate::Fixed<'a, Option<f32>>,
+        }
+
+        let s: String<64> = to_string(
+            &FixedFloats {
+                lat: crate::Fixed(&55.671_234_56, 6),
+                alt: crate::Fixed(&12.0, 0),
+                acc: crate::Fixed(&None, 2),
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s, String::<64>::from("AT+CMD=55.671235,12\r\n"));
+
+        // Any value fits, up to the maximum precision
+        let s: String<512> = to_string(
+            &crate::Fixed(&f64::MIN, 16),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(s.len(), crate::fixed_len::<f64>(16));
+
+        let res: Result<String<512>> = to_string(
+            &crate::Fixed(&1.5, 17),
+            String::<32>::from(""),
+            SerializeOptions::default(),
+        );
+        assert!(matches!(res, Err(Error::CustomError)));
+    }
+
+    #[test]
+    fn unsupported() {
+        #[derive(Clone, PartialEq, Serialize)]
+        struct WithVec {
+            v: heapless::Vec<u8, 4>,
+        }
+
+        let res: Result<String<32>> = to_string(
+            &WithVec {
+                v: heapless::Vec::from_slice(&[1, 2]).unwrap(),
+            },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        );
+        assert!(matches!(res, Err(Error::Unsupported)));
+
+        let res: Result<String<8>> = to_string(
+            &CmdWithLen { x: 12345, y: 67890 },
+            String::<32>::from("+CMD"),
+            SerializeOptions::default(),
+        );
+        assert!(matches!(res, Err(Error::BufferFull)));
+    }
+
+    #[test]
+    fn writers() {
+        #[derive(Clone, PartialEq, Serialize)]
+        struct WithOptions {
+            a: Option<u8>,
+            b: Option<u8>,
+            c: Option<u8>,
+        }
+
+        // Serial port accepting a limited number of bytes, which can't take
+        // back the separators of trailing empty parameters
+        struct Port {
+            sent: Vec<u8, 32>,
+            limit: usize,
+        }
+
+        impl Write for Port {
+            fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+                if self.sent.len() + bytes.len() > self.limit {
+                    return Err(Error::Write);
+                }
+                self.sent.write_bytes(bytes)
+            }
+        }
+
+        let mut port = Port {
+            sent: Vec::new(),
+            limit: 32,
+        };
+        let value = WithOptions {
+            a: None,
+            b: Some(2),
+            c: None,
+        };
+        let len = to_writer(&value, "+CMD", &mut port, SerializeOptions::default()).unwrap();
+        assert_eq!(&port.sent[..], b"AT+CMD=,2\r\n");
+        assert_eq!(len, port.sent.len());
+
+        let mut port = Port {
+            sent: Vec::new(),
+            limit: 4,
+        };
+        let res = to_writer(&value, "+CMD", &mut port, SerializeOptions::default());
+        assert!(matches!(res, Err(Error::Write)));
+        assert_eq!(&port.sent[..], b"AT");
+
+        let mut buf = [0; 16];
+        let len = to_slice(&value, "+CMD", &mut buf, SerializeOptions::default()).unwrap();
+        assert_eq!(&buf[..len], b"AT+CMD=,2\r\n");
+
+        let res = to_slice(&value, "+CMD", &mut buf[..8], SerializeOptions::default());
+        assert!(matches!(res, Err(Error::BufferFull)));
     }
 
     #[test]
@@ -612,43 +991,31 @@ mod tests {
         let b = WithBytes {
             s: Bytes(&slice[..]),
         };
-        let s: String<consts::U32> = to_string(
-            &b,
-            String::<consts::U32>::from("+CMD"),
-            SerializeOptions::default(),
-        )
-        .unwrap();
-        assert_eq!(s, String::<consts::U32>::from("AT+CMD=Some bytes\r\n"));
+        let s: String<32> =
+            to_string(&b, String::<32>::from("+CMD"), SerializeOptions::default()).unwrap();
+        assert_eq!(s, String::<32>::from("AT+CMD=Some bytes\r\n"));
     }
 
     #[test]
     fn char_vec_serialize() {
-        let test: CharVec<consts::U8> =
+        let test: CharVec<8> =
             CharVec(heapless::Vec::from_slice(&['I', 'M', 'P', '_', 'M', 'S', 'G']).unwrap());
 
-        let s: String<consts::U32> = to_string(
-            &test,
-            String::<consts::U32>::from(""),
-            SerializeOptions::default(),
-        )
-        .unwrap();
-        assert_eq!(s, String::<consts::U32>::from("IMP_MSG"));
+        let s: String<32> =
+            to_string(&test, String::<32>::from(""), SerializeOptions::default()).unwrap();
+        assert_eq!(s, String::<32>::from("IMP_MSG"));
 
         #[derive(Clone, PartialEq, Serialize)]
         pub struct WithCharVec {
-            s: CharVec<consts::U8>,
+            s: CharVec<8>,
             n: u8,
         }
         let b = WithCharVec {
             s: CharVec(heapless::Vec::from_slice(&['I', 'M', 'P', '_', 'M', 'S', 'G']).unwrap()),
             n: 12,
         };
-        let s: String<consts::U32> = to_string(
-            &b,
-            String::<consts::U32>::from("+CMD"),
-            SerializeOptions::default(),
-        )
-        .unwrap();
-        assert_eq!(s, String::<consts::U32>::from("AT+CMD=IMP_MSG,12\r\n"));
+        let s: String<32> =
+            to_string(&b, String::<32>::from("+CMD"), SerializeOptions::default()).unwrap();
+        assert_eq!(s, String::<32>::from("AT+CMD=IMP_MSG,12\r\n"));
     }
 }
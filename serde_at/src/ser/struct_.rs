@@ -1,34 +1,20 @@
 use serde::ser;
 
-use heapless::ArrayLength;
-
-use crate::ser::{Error, Result, Serializer};
+use crate::ser::{Error, Result, Serializer, Write};
 
 #[allow(clippy::module_name_repetitions)]
-pub struct SerializeStruct<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
-    ser: &'a mut Serializer<'b, B, C>,
+pub struct SerializeStruct<'a, 'b, W: Write + ?Sized> {
+    ser: &'a mut Serializer<'b, W>,
     first: bool,
 }
 
-impl<'a, 'b, B, C> SerializeStruct<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
-    pub(crate) fn new(ser: &'a mut Serializer<'b, B, C>) -> Self {
+impl<'a, 'b, W: Write + ?Sized> SerializeStruct<'a, 'b, W> {
+    pub(crate) fn new(ser: &'a mut Serializer<'b, W>) -> Self {
         SerializeStruct { ser, first: true }
     }
 }
 
-impl<'a, 'b, B, C> ser::SerializeStruct for SerializeStruct<'a, 'b, B, C>
-where
-    B: ArrayLength<u8>,
-    C: ArrayLength<u8>,
-{
+impl<'a, 'b, W: Write + ?Sized> ser::SerializeStruct for SerializeStruct<'a, 'b, W> {
     type Ok = ();
     type Error = Error;
 
@@ -36,23 +22,72 @@ where
     where
         T: ser::Serialize,
     {
-        if self.first {
+        ser::SerializeTupleStruct::serialize_field(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeTupleStruct::end(self)
+    }
+}
+
+impl<'a, 'b, W: Write + ?Sized> ser::SerializeTupleStruct for SerializeStruct<'a, 'b, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    where
+        T: ser::Serialize,
+    {
+        let sep = if self.first {
             if self.ser.options.value_sep {
-                self.ser.buf.push(b'=')?;
+                Some(b'=')
+            } else {
+                None
             }
         } else {
-            self.ser.buf.push(b',')?;
-        }
+            Some(b',')
+        };
         self.first = false;
 
-        value.serialize(&mut *self.ser)?;
-        Ok(())
+        self.ser.serialize_param(sep, value)
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.ser.trim_empty();
         self.ser
-            .buf
-            .extend_from_slice(self.ser.options.termination.as_bytes())?;
+            .write_bytes(self.ser.options.termination.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Tuples are serialized as comma separated values, without any prefix
+#[allow(clippy::module_name_repetitions)]
+pub struct SerializeTuple<'a, 'b, W: Write + ?Sized> {
+    ser: &'a mut Serializer<'b, W>,
+    first: bool,
+}
+
+impl<'a, 'b, W: Write + ?Sized> SerializeTuple<'a, 'b, W> {
+    pub(crate) fn new(ser: &'a mut Serializer<'b, W>) -> Self {
+        SerializeTuple { ser, first: true }
+    }
+}
+
+impl<'a, 'b, W: Write + ?Sized> ser::SerializeTuple for SerializeTuple<'a, 'b, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    where
+        T: ser::Serialize,
+    {
+        let sep = if self.first { None } else { Some(b',') };
+        self.first = false;
+
+        self.ser.serialize_param(sep, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
         Ok(())
     }
 }